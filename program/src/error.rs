@@ -0,0 +1,71 @@
+#![allow(deprecated)] // PrintProgramError and DecodeError, still what clients decode with
+
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
+use thiserror::Error;
+
+/// Errors raised by the escrow program, returned as `ProgramError::Custom`
+/// with the discriminant as code. Codes are stable: new errors go at the end.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+pub enum MetaLootProgramError {
+    #[error("token program account is not the SPL Token program")]
+    IncorrectTokenProgram = 0,
+    #[error("system program account is not the System program")]
+    IncorrectSystemProgram = 1,
+    #[error("account is not a token account of the SPL Token program")]
+    NotTokenAccount = 2,
+    #[error("mint account is not an SPL Token mint")]
+    NotMint = 3,
+    #[error("token account holds another mint than the escrow")]
+    MintMismatch = 4,
+    #[error("escrow token account is not controlled by the config PDA")]
+    EscrowAuthorityMismatch = 5,
+    #[error("escrow token account is not the one recorded in the config")]
+    EscrowMismatch = 6,
+    #[error("an account the instruction writes to is not writable")]
+    AccountNotWritable = 7,
+    #[error("escrow is not initialized")]
+    ConfigNotInitialized = 8,
+    #[error("config account is not the program's config PDA (wrong address or bump)")]
+    InvalidConfigAccount = 9,
+    #[error("signer is not the escrow admin")]
+    NotAdmin = 10,
+    #[error("escrow is paused")]
+    Paused = 11,
+    #[error("the admin cannot be handed to the default key")]
+    InvalidNewAdmin = 12,
+    #[error("this claim id has already been paid to the user")]
+    AlreadyClaimed = 13,
+    #[error("receipt account is not the claim's receipt PDA")]
+    InvalidReceiptAccount = 14,
+    #[error("program data account is not this upgradeable program's")]
+    InvalidProgramData = 15,
+    #[error("only the program's upgrade authority can initialize the escrow")]
+    NotUpgradeAuthority = 16,
+}
+
+impl From<MetaLootProgramError> for ProgramError {
+    fn from(err: MetaLootProgramError) -> Self {
+        ProgramError::Custom(err as u32)
+    }
+}
+
+impl<T> DecodeError<T> for MetaLootProgramError {
+    fn type_of() -> &'static str {
+        "MetaLootProgramError"
+    }
+}
+
+impl PrintProgramError for MetaLootProgramError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        msg!("Error: {}", &self.to_string());
+    }
+}
@@ -0,0 +1,227 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+/// Seed of the config PDA, which is also the escrow token account's authority.
+pub const CONFIG_SEED: &[u8] = b"config";
+/// Seed of the escrow token account PDA.
+pub const ESCROW_SEED: &[u8] = b"escrow";
+/// Seed of claim receipt PDAs, followed by the user and the claim id.
+pub const RECEIPT_SEED: &[u8] = b"receipt";
+
+/// Instructions of the escrow program, Borsh-serialised with the variant index
+/// as the first byte. Account lists are given in order under each variant.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum MetaLootInstruction {
+    /// Creates the escrow token account for a mint and the config account
+    /// recording it, the mint, the config PDA's canonical bump and the signer
+    /// as admin. Only the program's upgrade authority may sign, so nobody can
+    /// claim the admin role of a freshly deployed program.
+    ///
+    /// 0. `[signer, writable]` admin, pays the rent
+    /// 1. `[writable]` config PDA
+    /// 2. `[writable]` escrow token account PDA
+    /// 3. `[]` mint
+    /// 4. `[]` token program
+    /// 5. `[]` system program
+    /// 6. `[]` program data account of this program
+    Initialize,
+    /// Moves `amount` from the depositor's token account into escrow.
+    ///
+    /// 0. `[signer]` depositor
+    /// 1. `[writable]` depositor's token account
+    /// 2. `[writable]` escrow token account
+    /// 3. `[]` config PDA
+    /// 4. `[]` token program
+    Deposit { amount: u64 },
+    /// Pays `amount` from escrow back to a token account of the admin's
+    /// choosing. Works while paused, so funds can always be recovered.
+    ///
+    /// 0. `[signer]` admin
+    /// 1. `[]` config PDA
+    /// 2. `[writable]` escrow token account
+    /// 3. `[writable]` destination token account
+    /// 4. `[]` token program
+    Withdraw { amount: u64 },
+    /// Pays a reward of `amount` from escrow to a user's token account and
+    /// creates the receipt PDA of (token account owner, `claim_id`) in the
+    /// same instruction, so a claim id is paid at most once per user. The
+    /// admin pays the receipt's rent.
+    ///
+    /// 0. `[signer, writable]` admin
+    /// 1. `[]` config PDA
+    /// 2. `[writable]` escrow token account
+    /// 3. `[writable]` user token account
+    /// 4. `[]` token program
+    /// 5. `[writable]` claim receipt PDA
+    /// 6. `[]` system program
+    Claim { amount: u64, claim_id: u64 },
+    /// Hands the admin role to `new_admin`.
+    ///
+    /// 0. `[signer]` admin
+    /// 1. `[writable]` config PDA
+    SetAdmin { new_admin: Pubkey },
+    /// Stops (`true`) or resumes (`false`) deposits and claims.
+    ///
+    /// Accounts as for `SetAdmin`.
+    Pause { paused: bool },
+    /// Sends what is left in escrow to a token account, then closes the
+    /// escrow and config accounts, returning their rent to the admin.
+    ///
+    /// 0. `[signer, writable]` admin
+    /// 1. `[writable]` config PDA
+    /// 2. `[writable]` escrow token account
+    /// 3. `[writable]` destination token account
+    /// 4. `[]` token program
+    CloseEscrow,
+}
+
+pub fn find_config_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+pub fn find_escrow_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ESCROW_SEED], program_id)
+}
+
+/// Where the upgradeable loader keeps the program's code and upgrade authority.
+pub fn find_program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[program_id.as_ref()],
+        &solana_sdk_ids::bpf_loader_upgradeable::ID,
+    )
+    .0
+}
+
+pub fn find_claim_receipt_pda(program_id: &Pubkey, user: &Pubkey, claim_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RECEIPT_SEED, user.as_ref(), &claim_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+fn instruction(
+    program_id: &Pubkey,
+    data: MetaLootInstruction,
+    accounts: Vec<AccountMeta>,
+) -> Instruction {
+    Instruction::new_with_borsh(*program_id, &data, accounts)
+}
+
+pub fn initialize(program_id: &Pubkey, admin: &Pubkey, mint: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        MetaLootInstruction::Initialize,
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(find_config_pda(program_id).0, false),
+            AccountMeta::new(find_escrow_pda(program_id).0, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+            AccountMeta::new_readonly(find_program_data_address(program_id), false),
+        ],
+    )
+}
+
+pub fn deposit(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    source: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        MetaLootInstruction::Deposit { amount },
+        vec![
+            AccountMeta::new_readonly(*depositor, true),
+            AccountMeta::new(*source, false),
+            AccountMeta::new(find_escrow_pda(program_id).0, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+    )
+}
+
+pub fn withdraw(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        MetaLootInstruction::Withdraw { amount },
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+            AccountMeta::new(find_escrow_pda(program_id).0, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+    )
+}
+
+/// `user_token_account` must be owned by `user`, whose receipt is created.
+pub fn claim(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    amount: u64,
+    claim_id: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        MetaLootInstruction::Claim { amount, claim_id },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+            AccountMeta::new(find_escrow_pda(program_id).0, false),
+            AccountMeta::new(*user_token_account, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new(find_claim_receipt_pda(program_id, user, claim_id).0, false),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+        ],
+    )
+}
+
+pub fn set_admin(program_id: &Pubkey, admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        MetaLootInstruction::SetAdmin {
+            new_admin: *new_admin,
+        },
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+pub fn pause(program_id: &Pubkey, admin: &Pubkey, paused: bool) -> Instruction {
+    instruction(
+        program_id,
+        MetaLootInstruction::Pause { paused },
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+pub fn close_escrow(program_id: &Pubkey, admin: &Pubkey, destination: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        MetaLootInstruction::CloseEscrow,
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(find_config_pda(program_id).0, false),
+            AccountMeta::new(find_escrow_pda(program_id).0, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+    )
+}
@@ -0,0 +1,628 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::{rent::Rent, Sysvar},
+};
+use solana_system_interface::instruction as system_instruction;
+use spl_token::instruction as token_instruction;
+
+pub mod error;
+pub mod instruction;
+pub mod state;
+
+use error::MetaLootProgramError;
+use instruction::{MetaLootInstruction, CONFIG_SEED, ESCROW_SEED, RECEIPT_SEED};
+use state::{ClaimReceipt, Config};
+
+// Declare and export the program's entrypoint
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_and_print);
+
+/// Runs `process_instruction`, logging a failure by name so it can be read
+/// from the transaction logs as well as decoded from its code.
+#[cfg(not(feature = "no-entrypoint"))]
+#[allow(deprecated)]
+fn process_and_print(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    use solana_program::program_error::PrintProgramError;
+
+    process_instruction(program_id, accounts, instruction_data)
+        .inspect_err(|error| error.print::<MetaLootProgramError>())
+}
+
+// Program entrypoint's implementation
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let instruction = MetaLootInstruction::try_from_slice(instruction_data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    match instruction {
+        MetaLootInstruction::Initialize => {
+            msg!("Instruction: Initialize");
+            process_initialize(program_id, accounts)
+        }
+        MetaLootInstruction::Deposit { amount } => {
+            msg!("Instruction: Deposit");
+            process_deposit(program_id, accounts, amount)
+        }
+        MetaLootInstruction::Withdraw { amount } => {
+            msg!("Instruction: Withdraw");
+            process_payout(program_id, accounts, amount, None)
+        }
+        MetaLootInstruction::Claim { amount, claim_id } => {
+            msg!("Instruction: Claim");
+            process_payout(program_id, accounts, amount, Some(claim_id))
+        }
+        MetaLootInstruction::SetAdmin { new_admin } => {
+            msg!("Instruction: SetAdmin");
+            if new_admin == Pubkey::default() {
+                return Err(MetaLootProgramError::InvalidNewAdmin.into());
+            }
+            update_config(program_id, accounts, |config| config.admin = new_admin)
+        }
+        MetaLootInstruction::Pause { paused } => {
+            msg!("Instruction: Pause");
+            update_config(program_id, accounts, |config| config.paused = paused)
+        }
+        MetaLootInstruction::CloseEscrow => {
+            msg!("Instruction: CloseEscrow");
+            process_close_escrow(program_id, accounts)
+        }
+    }
+}
+
+fn check_signer(account: &AccountInfo) -> ProgramResult {
+    if !account.is_signer {
+        msg!("{} must sign the transaction", account.key);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+fn check_writable(account: &AccountInfo) -> ProgramResult {
+    if !account.is_writable {
+        msg!("{} must be writable", account.key);
+        return Err(MetaLootProgramError::AccountNotWritable.into());
+    }
+    Ok(())
+}
+
+fn check_key(
+    account: &AccountInfo,
+    expected: &Pubkey,
+    name: &str,
+    error: MetaLootProgramError,
+) -> ProgramResult {
+    if account.key != expected {
+        msg!("Wrong {}: expected {}, got {}", name, expected, account.key);
+        return Err(error.into());
+    }
+    Ok(())
+}
+
+fn check_token_program(account: &AccountInfo) -> ProgramResult {
+    if *account.key != spl_token::ID {
+        msg!("{} is not the token program", account.key);
+        return Err(MetaLootProgramError::IncorrectTokenProgram.into());
+    }
+    Ok(())
+}
+
+fn check_system_program(account: &AccountInfo) -> ProgramResult {
+    if *account.key != solana_system_interface::program::ID {
+        msg!("{} is not the system program", account.key);
+        return Err(MetaLootProgramError::IncorrectSystemProgram.into());
+    }
+    Ok(())
+}
+
+/// Unpacks a token account, checking that the token program owns it and that
+/// it holds `mint`.
+fn load_token_account(
+    account: &AccountInfo,
+    mint: &Pubkey,
+) -> Result<spl_token::state::Account, ProgramError> {
+    if *account.owner != spl_token::ID {
+        msg!("{} is not owned by the token program", account.key);
+        return Err(MetaLootProgramError::NotTokenAccount.into());
+    }
+    let token_account = spl_token::state::Account::unpack(&account.data.borrow())
+        .map_err(|_| MetaLootProgramError::NotTokenAccount)?;
+    if token_account.mint != *mint {
+        msg!(
+            "{} holds mint {}, not {}",
+            account.key,
+            token_account.mint,
+            mint
+        );
+        return Err(MetaLootProgramError::MintMismatch.into());
+    }
+    Ok(token_account)
+}
+
+/// Unpacks the escrow token account, checking it is the one in the config and
+/// that the config PDA is its authority.
+fn load_escrow(
+    config: &Config,
+    config_ai: &AccountInfo,
+    escrow_ai: &AccountInfo,
+) -> Result<spl_token::state::Account, ProgramError> {
+    if *escrow_ai.key != config.escrow {
+        msg!(
+            "Wrong escrow account: expected {}, got {}",
+            config.escrow,
+            escrow_ai.key
+        );
+        return Err(MetaLootProgramError::EscrowMismatch.into());
+    }
+    let escrow = load_token_account(escrow_ai, &config.mint)?;
+    if escrow.owner != *config_ai.key {
+        msg!("Escrow authority is {}, not the config PDA", escrow.owner);
+        return Err(MetaLootProgramError::EscrowAuthorityMismatch.into());
+    }
+    Ok(escrow)
+}
+
+/// Reads the config account, checking it is the program's config PDA.
+fn load_config(program_id: &Pubkey, config_ai: &AccountInfo) -> Result<Config, ProgramError> {
+    if config_ai.owner != program_id {
+        msg!("Config account is not initialized");
+        return Err(MetaLootProgramError::ConfigNotInitialized.into());
+    }
+    let config = Config::deserialize(&mut &config_ai.data.borrow()[..])
+        .map_err(|_| MetaLootProgramError::ConfigNotInitialized)?;
+    // The stored canonical bump spares a find_program_address search
+    let expected = Pubkey::create_program_address(&[CONFIG_SEED, &[config.bump]], program_id)
+        .map_err(|_| MetaLootProgramError::InvalidConfigAccount)?;
+    check_key(
+        config_ai,
+        &expected,
+        "config account",
+        MetaLootProgramError::InvalidConfigAccount,
+    )?;
+    Ok(config)
+}
+
+fn check_admin(config: &Config, admin_ai: &AccountInfo) -> ProgramResult {
+    check_signer(admin_ai)?;
+    if *admin_ai.key != config.admin {
+        msg!("{} is not the admin", admin_ai.key);
+        return Err(MetaLootProgramError::NotAdmin.into());
+    }
+    Ok(())
+}
+
+fn check_not_paused(config: &Config) -> ProgramResult {
+    if config.paused {
+        return Err(MetaLootProgramError::Paused.into());
+    }
+    Ok(())
+}
+
+/// Checks that `authority_ai` is the upgrade authority recorded in the
+/// program's ProgramData account.
+fn check_upgrade_authority(
+    program_id: &Pubkey,
+    program_data_ai: &AccountInfo,
+    authority_ai: &AccountInfo,
+) -> ProgramResult {
+    check_key(
+        program_data_ai,
+        &instruction::find_program_data_address(program_id),
+        "program data account",
+        MetaLootProgramError::InvalidProgramData,
+    )?;
+    if *program_data_ai.owner != solana_sdk_ids::bpf_loader_upgradeable::ID {
+        msg!(
+            "{} is not owned by the upgradeable loader",
+            program_data_ai.key
+        );
+        return Err(MetaLootProgramError::InvalidProgramData.into());
+    }
+    // Bincode layout of UpgradeableLoaderState::ProgramData: u32 variant 3,
+    // u64 slot, then the authority as an Option<Pubkey>
+    let data = program_data_ai.data.borrow();
+    let authority = match data.get(..45) {
+        Some(header) if header[..4] == 3u32.to_le_bytes() => match header[12] {
+            1 => Some(Pubkey::try_from(&header[13..45]).unwrap()),
+            _ => None,
+        },
+        _ => {
+            msg!("{} is not a ProgramData account", program_data_ai.key);
+            return Err(MetaLootProgramError::InvalidProgramData.into());
+        }
+    };
+    if authority != Some(*authority_ai.key) {
+        msg!("{} is not the upgrade authority", authority_ai.key);
+        return Err(MetaLootProgramError::NotUpgradeAuthority.into());
+    }
+    Ok(())
+}
+
+fn process_initialize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin_ai = next_account_info(account_info_iter)?;
+    let config_ai = next_account_info(account_info_iter)?;
+    let escrow_ai = next_account_info(account_info_iter)?;
+    let mint_ai = next_account_info(account_info_iter)?;
+    let token_program_ai = next_account_info(account_info_iter)?;
+    let system_program_ai = next_account_info(account_info_iter)?;
+    let program_data_ai = next_account_info(account_info_iter)?;
+
+    check_signer(admin_ai)?;
+    check_upgrade_authority(program_id, program_data_ai, admin_ai)?;
+    check_writable(admin_ai)?;
+    check_writable(config_ai)?;
+    check_writable(escrow_ai)?;
+    let (config_key, bump) = instruction::find_config_pda(program_id);
+    check_key(
+        config_ai,
+        &config_key,
+        "config account",
+        MetaLootProgramError::InvalidConfigAccount,
+    )?;
+    let (escrow_key, escrow_bump) = instruction::find_escrow_pda(program_id);
+    check_key(
+        escrow_ai,
+        &escrow_key,
+        "escrow account",
+        MetaLootProgramError::EscrowMismatch,
+    )?;
+    check_token_program(token_program_ai)?;
+    check_system_program(system_program_ai)?;
+    if *mint_ai.owner != spl_token::ID
+        || spl_token::state::Mint::unpack(&mint_ai.data.borrow()).is_err()
+    {
+        msg!("{} is not a mint", mint_ai.key);
+        return Err(MetaLootProgramError::NotMint.into());
+    }
+
+    let rent = Rent::get()?;
+    create_pda_account(
+        admin_ai,
+        config_ai,
+        system_program_ai,
+        rent.minimum_balance(Config::LEN),
+        Config::LEN,
+        program_id,
+        &[CONFIG_SEED, &[bump]],
+    )?;
+    create_pda_account(
+        admin_ai,
+        escrow_ai,
+        system_program_ai,
+        rent.minimum_balance(spl_token::state::Account::LEN),
+        spl_token::state::Account::LEN,
+        token_program_ai.key,
+        &[ESCROW_SEED, &[escrow_bump]],
+    )?;
+    invoke(
+        &token_instruction::initialize_account3(
+            token_program_ai.key,
+            escrow_ai.key,
+            mint_ai.key,
+            config_ai.key,
+        )?,
+        &[escrow_ai.clone(), mint_ai.clone(), token_program_ai.clone()],
+    )?;
+
+    Config {
+        admin: *admin_ai.key,
+        bump,
+        escrow: *escrow_ai.key,
+        mint: *mint_ai.key,
+        paused: false,
+    }
+    .serialize(&mut &mut config_ai.data.borrow_mut()[..])?;
+    msg!("Escrow initialized with admin {}", admin_ai.key);
+    Ok(())
+}
+
+fn process_deposit(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let depositor_ai = next_account_info(account_info_iter)?;
+    let source_ai = next_account_info(account_info_iter)?;
+    let escrow_ai = next_account_info(account_info_iter)?;
+    let config_ai = next_account_info(account_info_iter)?;
+    let token_program_ai = next_account_info(account_info_iter)?;
+
+    check_signer(depositor_ai)?;
+    check_writable(source_ai)?;
+    check_writable(escrow_ai)?;
+    let config = load_config(program_id, config_ai)?;
+    check_not_paused(&config)?;
+    check_token_program(token_program_ai)?;
+    load_escrow(&config, config_ai, escrow_ai)?;
+    load_token_account(source_ai, &config.mint)?;
+
+    invoke(
+        &token_instruction::transfer(
+            token_program_ai.key,
+            source_ai.key,
+            escrow_ai.key,
+            depositor_ai.key,
+            &[],
+            amount,
+        )?,
+        &[
+            source_ai.clone(),
+            escrow_ai.clone(),
+            depositor_ai.clone(),
+            token_program_ai.clone(),
+        ],
+    )?;
+    msg!("Deposited {} into escrow", amount);
+    Ok(())
+}
+
+/// Pays `amount` from escrow, for `Withdraw` and for `Claim`, which passes
+/// its `claim_id`. Only claims are stopped by a pause and leave a receipt.
+fn process_payout(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    claim_id: Option<u64>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin_ai = next_account_info(account_info_iter)?;
+    let config_ai = next_account_info(account_info_iter)?;
+    let escrow_ai = next_account_info(account_info_iter)?;
+    let destination_ai = next_account_info(account_info_iter)?;
+    let token_program_ai = next_account_info(account_info_iter)?;
+
+    let config = load_config(program_id, config_ai)?;
+    check_admin(&config, admin_ai)?;
+    if claim_id.is_some() {
+        check_not_paused(&config)?;
+    }
+    check_writable(escrow_ai)?;
+    check_writable(destination_ai)?;
+    check_token_program(token_program_ai)?;
+    load_escrow(&config, config_ai, escrow_ai)?;
+    let destination = load_token_account(destination_ai, &config.mint)?;
+
+    if let Some(claim_id) = claim_id {
+        let receipt_ai = next_account_info(account_info_iter)?;
+        let system_program_ai = next_account_info(account_info_iter)?;
+        check_writable(admin_ai)?;
+        check_system_program(system_program_ai)?;
+        let receipt = ClaimReceipt {
+            user: destination.owner,
+            claim_id,
+            amount,
+        };
+        create_receipt(
+            program_id,
+            admin_ai,
+            receipt_ai,
+            system_program_ai,
+            &receipt,
+        )?;
+    }
+
+    transfer_from_escrow(
+        config_ai,
+        escrow_ai,
+        destination_ai,
+        token_program_ai,
+        config.bump,
+        amount,
+    )?;
+    msg!("Paid {} from escrow to {}", amount, destination_ai.key);
+    Ok(())
+}
+
+/// Creates the receipt PDA of a claim and writes `receipt` to it. Fails if
+/// the receipt exists, i.e. the claim was paid already.
+fn create_receipt<'a>(
+    program_id: &Pubkey,
+    admin_ai: &AccountInfo<'a>,
+    receipt_ai: &AccountInfo<'a>,
+    system_program_ai: &AccountInfo<'a>,
+    receipt: &ClaimReceipt,
+) -> ProgramResult {
+    let (receipt_key, bump) =
+        instruction::find_claim_receipt_pda(program_id, &receipt.user, receipt.claim_id);
+    check_key(
+        receipt_ai,
+        &receipt_key,
+        "receipt account",
+        MetaLootProgramError::InvalidReceiptAccount,
+    )?;
+    check_writable(receipt_ai)?;
+    if receipt_ai.owner == program_id {
+        msg!(
+            "Claim {} of {} was paid already",
+            receipt.claim_id,
+            receipt.user
+        );
+        return Err(MetaLootProgramError::AlreadyClaimed.into());
+    }
+
+    let claim_id = receipt.claim_id.to_le_bytes();
+    create_pda_account(
+        admin_ai,
+        receipt_ai,
+        system_program_ai,
+        Rent::get()?.minimum_balance(ClaimReceipt::LEN),
+        ClaimReceipt::LEN,
+        program_id,
+        &[RECEIPT_SEED, receipt.user.as_ref(), &claim_id, &[bump]],
+    )?;
+    receipt.serialize(&mut &mut receipt_ai.data.borrow_mut()[..])?;
+    msg!("Receipt {} for claim {}", receipt_ai.key, receipt.claim_id);
+    Ok(())
+}
+
+/// Creates the PDA `target_ai`, signed for with `seeds`, with `space` bytes
+/// owned by `owner`, `payer_ai` funding it up to `lamports`. Lamports sent to
+/// the address beforehand would make `create_account` fail and block the
+/// instruction for good, so such an account is topped up, allocated and
+/// assigned instead.
+fn create_pda_account<'a>(
+    payer_ai: &AccountInfo<'a>,
+    target_ai: &AccountInfo<'a>,
+    system_program_ai: &AccountInfo<'a>,
+    lamports: u64,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    if target_ai.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer_ai.key,
+                target_ai.key,
+                lamports,
+                space as u64,
+                owner,
+            ),
+            &[
+                payer_ai.clone(),
+                target_ai.clone(),
+                system_program_ai.clone(),
+            ],
+            &[seeds],
+        );
+    }
+    let shortfall = lamports.saturating_sub(target_ai.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer_ai.key, target_ai.key, shortfall),
+            &[
+                payer_ai.clone(),
+                target_ai.clone(),
+                system_program_ai.clone(),
+            ],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(target_ai.key, space as u64),
+        &[target_ai.clone(), system_program_ai.clone()],
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(target_ai.key, owner),
+        &[target_ai.clone(), system_program_ai.clone()],
+        &[seeds],
+    )
+}
+
+/// Transfers `amount` out of escrow, signed by the config PDA.
+fn transfer_from_escrow<'a>(
+    config_ai: &AccountInfo<'a>,
+    escrow_ai: &AccountInfo<'a>,
+    destination_ai: &AccountInfo<'a>,
+    token_program_ai: &AccountInfo<'a>,
+    bump: u8,
+    amount: u64,
+) -> ProgramResult {
+    let transfer_ix = token_instruction::transfer(
+        token_program_ai.key,
+        escrow_ai.key,
+        destination_ai.key,
+        config_ai.key, // authority is the PDA
+        &[],
+        amount,
+    )?;
+
+    // Because the PDA does not have a private key, we must call invoke_signed with the PDA seeds.
+    invoke_signed(
+        &transfer_ix,
+        &[
+            escrow_ai.clone(),
+            destination_ai.clone(),
+            config_ai.clone(),
+            token_program_ai.clone(),
+        ],
+        &[&[CONFIG_SEED, &[bump]]],
+    )
+}
+
+/// Applies `change` to the config, for the admin-only settings.
+fn update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    change: impl FnOnce(&mut Config),
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin_ai = next_account_info(account_info_iter)?;
+    let config_ai = next_account_info(account_info_iter)?;
+
+    check_writable(config_ai)?;
+    let mut config = load_config(program_id, config_ai)?;
+    check_admin(&config, admin_ai)?;
+    change(&mut config);
+    config.serialize(&mut &mut config_ai.data.borrow_mut()[..])?;
+    msg!("Admin {}, paused {}", config.admin, config.paused);
+    Ok(())
+}
+
+fn process_close_escrow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin_ai = next_account_info(account_info_iter)?;
+    let config_ai = next_account_info(account_info_iter)?;
+    let escrow_ai = next_account_info(account_info_iter)?;
+    let destination_ai = next_account_info(account_info_iter)?;
+    let token_program_ai = next_account_info(account_info_iter)?;
+
+    check_writable(admin_ai)?;
+    check_writable(config_ai)?;
+    check_writable(escrow_ai)?;
+    check_writable(destination_ai)?;
+    let config = load_config(program_id, config_ai)?;
+    check_admin(&config, admin_ai)?;
+    check_token_program(token_program_ai)?;
+    let remaining = load_escrow(&config, config_ai, escrow_ai)?.amount;
+    load_token_account(destination_ai, &config.mint)?;
+
+    if remaining > 0 {
+        transfer_from_escrow(
+            config_ai,
+            escrow_ai,
+            destination_ai,
+            token_program_ai,
+            config.bump,
+            remaining,
+        )?;
+    }
+    invoke_signed(
+        &token_instruction::close_account(
+            token_program_ai.key,
+            escrow_ai.key,
+            admin_ai.key,
+            config_ai.key,
+            &[],
+        )?,
+        &[
+            escrow_ai.clone(),
+            admin_ai.clone(),
+            config_ai.clone(),
+            token_program_ai.clone(),
+        ],
+        &[&[CONFIG_SEED, &[config.bump]]],
+    )?;
+
+    // Emptied of lamports, the config account is removed at the end of the transaction
+    let lamports = config_ai.lamports();
+    **config_ai.try_borrow_mut_lamports()? = 0;
+    **admin_ai.try_borrow_mut_lamports()? += lamports;
+    config_ai.data.borrow_mut().fill(0);
+    msg!(
+        "Escrow closed, {} returned to {}",
+        remaining,
+        destination_ai.key
+    );
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+/// Settings of the escrow, stored in the config PDA.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// The only key allowed to pay out, pause or close the escrow.
+    pub admin: Pubkey,
+    /// Canonical bump of the config PDA, found once by `Initialize`.
+    pub bump: u8,
+    /// The escrow token account, whose authority is the config PDA.
+    pub escrow: Pubkey,
+    /// Mint of the tokens held in escrow.
+    pub mint: Pubkey,
+    /// Deposits and claims are refused while set.
+    pub paused: bool,
+}
+
+impl Config {
+    /// Serialised size in bytes.
+    pub const LEN: usize = 32 + 1 + 32 + 32 + 1;
+}
+
+/// Proof that a claim was paid, stored in the receipt PDA of
+/// (`user`, `claim_id`). Its existence is what stops a second payout.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ClaimReceipt {
+    /// Owner of the token account the reward went to.
+    pub user: Pubkey,
+    /// Id the backend gave the claim, unique per user.
+    pub claim_id: u64,
+    /// Base units paid.
+    pub amount: u64,
+}
+
+impl ClaimReceipt {
+    /// Serialised size in bytes.
+    pub const LEN: usize = 32 + 8 + 8;
+}
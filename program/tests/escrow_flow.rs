@@ -0,0 +1,443 @@
+//! Runs the escrow program in a test bank against the real SPL Token program
+//! and checks the balances each instruction leaves behind.
+
+use metaloot_escrow::{
+    error::MetaLootProgramError,
+    instruction::{self, find_claim_receipt_pda, find_config_pda, find_escrow_pda},
+    process_instruction,
+    state::{ClaimReceipt, Config},
+};
+use solana_program::{program_option::COption, program_pack::Pack};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+
+const SUPPLY: u64 = 1_000;
+
+fn packed<T: Pack>(state: T) -> Vec<u8> {
+    let mut data = vec![0; T::LEN];
+    state.pack_into_slice(&mut data);
+    data
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    Account {
+        lamports: 1_000_000_000,
+        data: packed(TokenAccount {
+            mint,
+            owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        }),
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn wallet() -> Account {
+    Account::new(10_000_000_000, 0, &solana_system_interface::program::ID)
+}
+
+/// A bank with the escrow program, whose upgrade authority is `admin`, a mint
+/// whose whole supply sits in the admin's token account, and an empty token
+/// account for `user`.
+struct Harness {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    admin: Keypair,
+    stranger: Keypair,
+    mint: Pubkey,
+    admin_tokens: Pubkey,
+    user: Pubkey,
+    user_tokens: Pubkey,
+}
+
+impl Harness {
+    /// Starts the bank; with `prefund_pdas` the config and escrow PDAs already
+    /// hold some lamports, as anyone could send them before Initialize.
+    async fn start(prefund_pdas: bool) -> Self {
+        let program_id = Pubkey::new_unique();
+        let mut program_test = ProgramTest::new(
+            "metaloot_escrow",
+            program_id,
+            processor!(process_instruction),
+        );
+        let admin = Keypair::new();
+        let stranger = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let admin_tokens = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let user_tokens = Pubkey::new_unique();
+
+        program_test.add_account(admin.pubkey(), wallet());
+        program_test.add_account(stranger.pubkey(), wallet());
+        // ProgramData of an upgradeable program: variant 3, deploy slot, authority
+        let mut program_data = 3u32.to_le_bytes().to_vec();
+        program_data.extend(0u64.to_le_bytes());
+        program_data.push(1);
+        program_data.extend(admin.pubkey().to_bytes());
+        program_test.add_account(
+            instruction::find_program_data_address(&program_id),
+            Account {
+                lamports: 1_000_000_000,
+                data: program_data,
+                owner: solana_sdk_ids::bpf_loader_upgradeable::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        program_test.add_account(
+            mint,
+            Account {
+                lamports: 1_000_000_000,
+                data: packed(Mint {
+                    mint_authority: COption::None,
+                    supply: SUPPLY,
+                    decimals: 0,
+                    is_initialized: true,
+                    freeze_authority: COption::None,
+                }),
+                owner: spl_token::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        program_test.add_account(admin_tokens, token_account(mint, admin.pubkey(), SUPPLY));
+        program_test.add_account(user_tokens, token_account(mint, user, 0));
+        if prefund_pdas {
+            for pda in [
+                find_config_pda(&program_id).0,
+                find_escrow_pda(&program_id).0,
+            ] {
+                program_test.add_account(
+                    pda,
+                    Account::new(1_000, 0, &solana_system_interface::program::ID),
+                );
+            }
+        }
+
+        Harness {
+            context: program_test.start_with_context().await,
+            program_id,
+            admin,
+            stranger,
+            mint,
+            admin_tokens,
+            user,
+            user_tokens,
+        }
+    }
+
+    /// Starts the bank, initializes the escrow and deposits `deposit` tokens.
+    async fn initialized(deposit: u64) -> Self {
+        let mut harness = Self::start(false).await;
+        let initialize = harness.initialize();
+        harness.send(initialize, &[]).await.unwrap();
+        let deposit = instruction::deposit(
+            &harness.program_id,
+            &harness.admin.pubkey(),
+            &harness.admin_tokens,
+            deposit,
+        );
+        harness.send(deposit, &[]).await.unwrap();
+        harness
+    }
+
+    fn initialize(&self) -> Instruction {
+        instruction::initialize(&self.program_id, &self.admin.pubkey(), &self.mint)
+    }
+
+    fn claim(&self, amount: u64, claim_id: u64) -> Instruction {
+        instruction::claim(
+            &self.program_id,
+            &self.admin.pubkey(),
+            &self.user,
+            &self.user_tokens,
+            amount,
+            claim_id,
+        )
+    }
+
+    /// Sends `instruction` signed by the admin, or by `signer` when given.
+    async fn send(
+        &mut self,
+        instruction: Instruction,
+        signer: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let signer = signer.first().copied().unwrap_or(&self.admin);
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.context.payer.pubkey()),
+            &[&self.context.payer, signer],
+            self.context.last_blockhash,
+        );
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    async fn balance(&mut self, token_account: Pubkey) -> u64 {
+        self.context
+            .banks_client
+            .get_packed_account_data::<TokenAccount>(token_account)
+            .await
+            .unwrap()
+            .amount
+    }
+
+    async fn escrow_balance(&mut self) -> u64 {
+        self.balance(find_escrow_pda(&self.program_id).0).await
+    }
+
+    async fn config(&mut self) -> Config {
+        let account = self
+            .context
+            .banks_client
+            .get_account(find_config_pda(&self.program_id).0)
+            .await
+            .unwrap()
+            .unwrap();
+        borsh::from_slice(&account.data).unwrap()
+    }
+}
+
+fn assert_program_error(result: Result<(), BanksClientError>, expected: MetaLootProgramError) {
+    assert_instruction_error(result, InstructionError::Custom(expected as u32));
+}
+
+fn assert_instruction_error(result: Result<(), BanksClientError>, expected: InstructionError) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, expected)
+    );
+}
+
+#[tokio::test]
+async fn initialize_and_deposit_move_tokens_into_escrow() {
+    let mut harness = Harness::initialized(400).await;
+    let config = harness.config().await;
+    assert_eq!(config.admin, harness.admin.pubkey());
+    assert_eq!(config.mint, harness.mint);
+    assert!(!config.paused);
+    assert_eq!(harness.escrow_balance().await, 400);
+    assert_eq!(harness.balance(harness.admin_tokens).await, SUPPLY - 400);
+}
+
+#[tokio::test]
+async fn initialize_succeeds_over_prefunded_pdas() {
+    let mut harness = Harness::start(true).await;
+    let initialize = harness.initialize();
+    harness.send(initialize, &[]).await.unwrap();
+    assert_eq!(harness.config().await.admin, harness.admin.pubkey());
+    assert_eq!(harness.escrow_balance().await, 0);
+}
+
+#[tokio::test]
+async fn initialize_rejects_signer_other_than_upgrade_authority() {
+    let mut harness = Harness::start(false).await;
+    let initialize = instruction::initialize(
+        &harness.program_id,
+        &harness.stranger.pubkey(),
+        &harness.mint,
+    );
+    let stranger = harness.stranger.insecure_clone();
+    assert_program_error(
+        harness.send(initialize, &[&stranger]).await,
+        MetaLootProgramError::NotUpgradeAuthority,
+    );
+}
+
+#[tokio::test]
+async fn malformed_instruction_data_is_rejected() {
+    let mut harness = Harness::initialized(100).await;
+    let deposit = instruction::deposit(
+        &harness.program_id,
+        &harness.admin.pubkey(),
+        &harness.admin_tokens,
+        10,
+    );
+    let truncated = deposit.data[..5].to_vec();
+    for data in [Vec::new(), vec![42], truncated] {
+        let malformed = Instruction {
+            data,
+            ..deposit.clone()
+        };
+        assert_instruction_error(
+            harness.send(malformed, &[]).await,
+            InstructionError::InvalidInstructionData,
+        );
+    }
+    assert_eq!(harness.escrow_balance().await, 100);
+}
+
+#[tokio::test]
+async fn withdraw_and_claim_pay_out_of_escrow() {
+    let mut harness = Harness::initialized(500).await;
+    let withdraw = instruction::withdraw(
+        &harness.program_id,
+        &harness.admin.pubkey(),
+        &harness.admin_tokens,
+        100,
+    );
+    harness.send(withdraw, &[]).await.unwrap();
+    let claim = harness.claim(50, 1);
+    harness.send(claim, &[]).await.unwrap();
+
+    assert_eq!(harness.escrow_balance().await, 350);
+    assert_eq!(harness.balance(harness.admin_tokens).await, SUPPLY - 400);
+    assert_eq!(harness.balance(harness.user_tokens).await, 50);
+    let receipt = harness
+        .context
+        .banks_client
+        .get_account(find_claim_receipt_pda(&harness.program_id, &harness.user, 1).0)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        borsh::from_slice::<ClaimReceipt>(&receipt.data).unwrap(),
+        ClaimReceipt {
+            user: harness.user,
+            claim_id: 1,
+            amount: 50,
+        }
+    );
+
+    let claim_again = harness.claim(60, 1);
+    assert_program_error(
+        harness.send(claim_again, &[]).await,
+        MetaLootProgramError::AlreadyClaimed,
+    );
+    assert_eq!(harness.balance(harness.user_tokens).await, 50);
+}
+
+#[tokio::test]
+async fn pause_blocks_claims_but_not_withdrawals() {
+    let mut harness = Harness::initialized(500).await;
+    let pause = instruction::pause(&harness.program_id, &harness.admin.pubkey(), true);
+    harness.send(pause, &[]).await.unwrap();
+    assert!(harness.config().await.paused);
+
+    let claim = harness.claim(50, 1);
+    assert_program_error(harness.send(claim, &[]).await, MetaLootProgramError::Paused);
+    let withdraw = instruction::withdraw(
+        &harness.program_id,
+        &harness.admin.pubkey(),
+        &harness.admin_tokens,
+        100,
+    );
+    harness.send(withdraw, &[]).await.unwrap();
+    assert_eq!(harness.escrow_balance().await, 400);
+    assert_eq!(harness.balance(harness.user_tokens).await, 0);
+
+    let resume = instruction::pause(&harness.program_id, &harness.admin.pubkey(), false);
+    harness.send(resume, &[]).await.unwrap();
+    let claim = harness.claim(50, 2);
+    harness.send(claim, &[]).await.unwrap();
+    assert_eq!(harness.escrow_balance().await, 350);
+    assert_eq!(harness.balance(harness.user_tokens).await, 50);
+}
+
+#[tokio::test]
+async fn set_admin_hands_over_control() {
+    let mut harness = Harness::initialized(500).await;
+    let new_admin = harness.stranger.insecure_clone();
+    let set_admin = instruction::set_admin(
+        &harness.program_id,
+        &harness.admin.pubkey(),
+        &new_admin.pubkey(),
+    );
+    harness.send(set_admin, &[]).await.unwrap();
+    assert_eq!(harness.config().await.admin, new_admin.pubkey());
+
+    let old_admin_pause = instruction::pause(&harness.program_id, &harness.admin.pubkey(), true);
+    assert_program_error(
+        harness.send(old_admin_pause, &[]).await,
+        MetaLootProgramError::NotAdmin,
+    );
+    let new_admin_pause = instruction::pause(&harness.program_id, &new_admin.pubkey(), true);
+    harness.send(new_admin_pause, &[&new_admin]).await.unwrap();
+    assert!(harness.config().await.paused);
+
+    let to_nobody =
+        instruction::set_admin(&harness.program_id, &new_admin.pubkey(), &Pubkey::default());
+    assert_program_error(
+        harness.send(to_nobody, &[&new_admin]).await,
+        MetaLootProgramError::InvalidNewAdmin,
+    );
+}
+
+#[tokio::test]
+async fn non_admin_cannot_update_config_or_pay_out() {
+    let mut harness = Harness::initialized(500).await;
+    let stranger = harness.stranger.insecure_clone();
+    let attempts = [
+        instruction::set_admin(&harness.program_id, &stranger.pubkey(), &stranger.pubkey()),
+        instruction::pause(&harness.program_id, &stranger.pubkey(), true),
+        instruction::withdraw(
+            &harness.program_id,
+            &stranger.pubkey(),
+            &harness.user_tokens,
+            100,
+        ),
+        instruction::close_escrow(
+            &harness.program_id,
+            &stranger.pubkey(),
+            &harness.user_tokens,
+        ),
+    ];
+    for attempt in attempts {
+        assert_program_error(
+            harness.send(attempt, &[&stranger]).await,
+            MetaLootProgramError::NotAdmin,
+        );
+    }
+    let config = harness.config().await;
+    assert_eq!(config.admin, harness.admin.pubkey());
+    assert!(!config.paused);
+    assert_eq!(harness.escrow_balance().await, 500);
+}
+
+#[tokio::test]
+async fn close_escrow_returns_tokens_and_rent() {
+    let mut harness = Harness::initialized(300).await;
+    let admin_lamports = harness
+        .context
+        .banks_client
+        .get_balance(harness.admin.pubkey())
+        .await
+        .unwrap();
+    let close = instruction::close_escrow(
+        &harness.program_id,
+        &harness.admin.pubkey(),
+        &harness.admin_tokens,
+    );
+    harness.send(close, &[]).await.unwrap();
+
+    assert_eq!(harness.balance(harness.admin_tokens).await, SUPPLY);
+    for closed in [
+        find_config_pda(&harness.program_id).0,
+        find_escrow_pda(&harness.program_id).0,
+    ] {
+        let account = harness.context.banks_client.get_account(closed).await;
+        assert_eq!(account.unwrap(), None);
+    }
+    let refunded = harness
+        .context
+        .banks_client
+        .get_balance(harness.admin.pubkey())
+        .await
+        .unwrap();
+    assert!(refunded > admin_lamports);
+}
@@ -0,0 +1,531 @@
+//! Each test hands the escrow program one spoofed account or setting and
+//! checks it is rejected with the matching named error before any token moves.
+
+use metaloot_escrow::{
+    error::MetaLootProgramError,
+    instruction::{
+        find_claim_receipt_pda, find_config_pda, find_escrow_pda, find_program_data_address,
+        MetaLootInstruction,
+    },
+    process_instruction,
+    state::{ClaimReceipt, Config},
+};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    program_option::COption, program_pack::Pack, program_stubs, pubkey::Pubkey, rent::Rent,
+};
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+use std::sync::Once;
+
+/// Native builds have no rent sysvar; this hands out the default one.
+struct RentStubs;
+
+impl program_stubs::SyscallStubs for RentStubs {
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+}
+
+fn install_rent_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(RentStubs));
+    });
+}
+
+struct TestAccount {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+impl TestAccount {
+    fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+        TestAccount {
+            key,
+            owner,
+            lamports: 1_000_000,
+            data,
+            is_signer: false,
+            is_writable: true,
+        }
+    }
+
+    fn signer(key: Pubkey) -> Self {
+        TestAccount {
+            is_signer: true,
+            ..TestAccount::new(key, solana_system_interface::program::ID, Vec::new())
+        }
+    }
+
+    fn program(key: Pubkey) -> Self {
+        TestAccount {
+            is_writable: false,
+            ..TestAccount::new(key, Pubkey::default(), Vec::new())
+        }
+    }
+
+    fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            self.is_writable,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
+
+fn token_account_data(mint: Pubkey, owner: Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0; TokenAccount::LEN];
+    TokenAccount {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    data
+}
+
+fn mint_data() -> Vec<u8> {
+    let mut data = vec![0; Mint::LEN];
+    Mint {
+        mint_authority: COption::None,
+        supply: 1_000,
+        decimals: 0,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    data
+}
+
+/// An initialized escrow holding 1000 tokens.
+struct Escrow {
+    program_id: Pubkey,
+    admin: Pubkey,
+    mint: Pubkey,
+    config: Pubkey,
+    escrow: Pubkey,
+    user: Pubkey,
+}
+
+impl Escrow {
+    fn new() -> Self {
+        install_rent_stubs();
+        let program_id = Pubkey::new_unique();
+        Escrow {
+            program_id,
+            admin: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            config: find_config_pda(&program_id).0,
+            escrow: find_escrow_pda(&program_id).0,
+            user: Pubkey::new_unique(),
+        }
+    }
+
+    fn config_account(&self) -> TestAccount {
+        let config = Config {
+            admin: self.admin,
+            bump: find_config_pda(&self.program_id).1,
+            escrow: self.escrow,
+            mint: self.mint,
+            paused: false,
+        };
+        TestAccount::new(
+            self.config,
+            self.program_id,
+            borsh::to_vec(&config).unwrap(),
+        )
+    }
+
+    fn escrow_account(&self) -> TestAccount {
+        TestAccount::new(
+            self.escrow,
+            spl_token::ID,
+            token_account_data(self.mint, self.config, 1_000),
+        )
+    }
+
+    fn user_account(&self) -> TestAccount {
+        TestAccount::new(
+            Pubkey::new_unique(),
+            spl_token::ID,
+            token_account_data(self.mint, self.user, 0),
+        )
+    }
+
+    /// The receipt PDA of `CLAIM_ID` before the claim. The stubbed system
+    /// program does not allocate, so its data is sized up front.
+    fn receipt_account(&self) -> TestAccount {
+        TestAccount {
+            lamports: 0,
+            ..TestAccount::new(
+                find_claim_receipt_pda(&self.program_id, &self.user, CLAIM_ID).0,
+                solana_system_interface::program::ID,
+                vec![0; ClaimReceipt::LEN],
+            )
+        }
+    }
+
+    /// Accounts of `Claim`: admin, config, escrow, user token account, token
+    /// program, receipt and system program.
+    fn claim_accounts(&self) -> Vec<TestAccount> {
+        vec![
+            TestAccount::signer(self.admin),
+            self.config_account(),
+            self.escrow_account(),
+            self.user_account(),
+            TestAccount::program(spl_token::ID),
+            self.receipt_account(),
+            TestAccount::program(solana_system_interface::program::ID),
+        ]
+    }
+
+    /// The ProgramData account of the program, with `authority` as upgrade
+    /// authority.
+    fn program_data_account(&self, authority: Option<Pubkey>) -> TestAccount {
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend(0u64.to_le_bytes());
+        match authority {
+            Some(authority) => {
+                data.push(1);
+                data.extend(authority.to_bytes());
+            }
+            None => data.push(0),
+        }
+        // The loader reserves room for an authority either way
+        data.resize(45, 0);
+        TestAccount {
+            is_writable: false,
+            ..TestAccount::new(
+                find_program_data_address(&self.program_id),
+                solana_sdk_ids::bpf_loader_upgradeable::ID,
+                data,
+            )
+        }
+    }
+
+    /// Accounts of `Initialize`: admin, config, escrow, mint, token program,
+    /// system program and program data.
+    fn initialize_accounts(&self) -> Vec<TestAccount> {
+        vec![
+            TestAccount::signer(self.admin),
+            TestAccount::new(
+                self.config,
+                solana_system_interface::program::ID,
+                Vec::new(),
+            ),
+            TestAccount::new(
+                self.escrow,
+                solana_system_interface::program::ID,
+                Vec::new(),
+            ),
+            TestAccount::new(self.mint, spl_token::ID, mint_data()),
+            TestAccount::program(spl_token::ID),
+            TestAccount::program(solana_system_interface::program::ID),
+            self.program_data_account(Some(self.admin)),
+        ]
+    }
+
+    fn run(&self, accounts: &mut [TestAccount], instruction: MetaLootInstruction) -> ProgramResult {
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+        process_instruction(
+            &self.program_id,
+            &infos,
+            &borsh::to_vec(&instruction).unwrap(),
+        )
+    }
+
+    fn claim(&self, accounts: &mut [TestAccount]) -> ProgramResult {
+        self.run(
+            accounts,
+            MetaLootInstruction::Claim {
+                amount: 10,
+                claim_id: CLAIM_ID,
+            },
+        )
+    }
+}
+
+fn edit_config(account: &mut TestAccount, edit: impl FnOnce(&mut Config)) {
+    let mut config: Config = borsh::from_slice(&account.data).unwrap();
+    edit(&mut config);
+    account.data = borsh::to_vec(&config).unwrap();
+}
+
+fn assert_error(result: ProgramResult, expected: MetaLootProgramError) {
+    assert_eq!(result, Err(ProgramError::from(expected)));
+}
+
+const CLAIM_ADMIN: usize = 0;
+const CLAIM_CONFIG: usize = 1;
+const CLAIM_ESCROW: usize = 2;
+const CLAIM_USER: usize = 3;
+const CLAIM_TOKEN_PROGRAM: usize = 4;
+const CLAIM_RECEIPT: usize = 5;
+const CLAIM_ID: u64 = 7;
+
+#[test]
+fn claim_with_genuine_accounts_passes_validation() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    assert_eq!(escrow.claim(&mut accounts), Ok(()));
+}
+
+#[test]
+fn claim_rejects_fake_token_program() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    accounts[CLAIM_TOKEN_PROGRAM] = TestAccount::program(Pubkey::new_unique());
+    assert_error(
+        escrow.claim(&mut accounts),
+        MetaLootProgramError::IncorrectTokenProgram,
+    );
+}
+
+#[test]
+fn claim_rejects_escrow_not_owned_by_token_program() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    accounts[CLAIM_ESCROW].owner = Pubkey::new_unique();
+    assert_error(
+        escrow.claim(&mut accounts),
+        MetaLootProgramError::NotTokenAccount,
+    );
+}
+
+#[test]
+fn claim_rejects_escrow_with_other_authority() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    accounts[CLAIM_ESCROW].data = token_account_data(escrow.mint, Pubkey::new_unique(), 1_000);
+    assert_error(
+        escrow.claim(&mut accounts),
+        MetaLootProgramError::EscrowAuthorityMismatch,
+    );
+}
+
+#[test]
+fn claim_rejects_escrow_of_other_mint() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    accounts[CLAIM_ESCROW].data = token_account_data(Pubkey::new_unique(), escrow.config, 1_000);
+    assert_error(
+        escrow.claim(&mut accounts),
+        MetaLootProgramError::MintMismatch,
+    );
+}
+
+#[test]
+fn claim_rejects_substituted_escrow_account() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    // A genuine token account under the config PDA, but not the recorded escrow
+    accounts[CLAIM_ESCROW].key = Pubkey::new_unique();
+    assert_error(
+        escrow.claim(&mut accounts),
+        MetaLootProgramError::EscrowMismatch,
+    );
+}
+
+#[test]
+fn claim_rejects_user_account_of_other_mint() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    accounts[CLAIM_USER].data = token_account_data(Pubkey::new_unique(), Pubkey::new_unique(), 0);
+    assert_error(
+        escrow.claim(&mut accounts),
+        MetaLootProgramError::MintMismatch,
+    );
+}
+
+#[test]
+fn claim_rejects_user_account_not_owned_by_token_program() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    accounts[CLAIM_USER].owner = Pubkey::new_unique();
+    assert_error(
+        escrow.claim(&mut accounts),
+        MetaLootProgramError::NotTokenAccount,
+    );
+}
+
+#[test]
+fn claim_rejects_signer_other_than_admin() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    accounts[CLAIM_ADMIN] = TestAccount::signer(Pubkey::new_unique());
+    assert_error(escrow.claim(&mut accounts), MetaLootProgramError::NotAdmin);
+}
+
+#[test]
+fn claim_rejects_config_with_wrong_bump() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    edit_config(&mut accounts[CLAIM_CONFIG], |config| {
+        config.bump = config.bump.wrapping_sub(1)
+    });
+    assert_error(
+        escrow.claim(&mut accounts),
+        MetaLootProgramError::InvalidConfigAccount,
+    );
+}
+
+#[test]
+fn claim_rejects_while_paused() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    edit_config(&mut accounts[CLAIM_CONFIG], |config| config.paused = true);
+    assert_error(escrow.claim(&mut accounts), MetaLootProgramError::Paused);
+}
+
+#[test]
+fn claim_writes_receipt() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    assert_eq!(escrow.claim(&mut accounts), Ok(()));
+    let receipt: ClaimReceipt = borsh::from_slice(&accounts[CLAIM_RECEIPT].data).unwrap();
+    assert_eq!(
+        receipt,
+        ClaimReceipt {
+            user: escrow.user,
+            claim_id: CLAIM_ID,
+            amount: 10,
+        }
+    );
+}
+
+#[test]
+fn claim_rejects_second_claim_with_same_id() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    // What the first claim leaves behind
+    accounts[CLAIM_RECEIPT].owner = escrow.program_id;
+    accounts[CLAIM_RECEIPT].lamports = 1_000_000;
+    assert_error(
+        escrow.claim(&mut accounts),
+        MetaLootProgramError::AlreadyClaimed,
+    );
+}
+
+#[test]
+fn claim_rejects_receipt_of_other_claim_id() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.claim_accounts();
+    accounts[CLAIM_RECEIPT].key =
+        find_claim_receipt_pda(&escrow.program_id, &escrow.user, CLAIM_ID + 1).0;
+    assert_error(
+        escrow.claim(&mut accounts),
+        MetaLootProgramError::InvalidReceiptAccount,
+    );
+}
+
+#[test]
+fn deposit_rejects_source_of_other_mint() {
+    let escrow = Escrow::new();
+    let mut accounts = vec![
+        TestAccount::signer(Pubkey::new_unique()),
+        TestAccount::new(
+            Pubkey::new_unique(),
+            spl_token::ID,
+            token_account_data(Pubkey::new_unique(), Pubkey::new_unique(), 10),
+        ),
+        escrow.escrow_account(),
+        escrow.config_account(),
+        TestAccount::program(spl_token::ID),
+    ];
+    assert_error(
+        escrow.run(&mut accounts, MetaLootInstruction::Deposit { amount: 10 }),
+        MetaLootProgramError::MintMismatch,
+    );
+}
+
+#[test]
+fn initialize_rejects_fake_token_program() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.initialize_accounts();
+    accounts[4] = TestAccount::program(Pubkey::new_unique());
+    assert_error(
+        escrow.run(&mut accounts, MetaLootInstruction::Initialize),
+        MetaLootProgramError::IncorrectTokenProgram,
+    );
+}
+
+#[test]
+fn initialize_rejects_fake_system_program() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.initialize_accounts();
+    accounts[5] = TestAccount::program(Pubkey::new_unique());
+    assert_error(
+        escrow.run(&mut accounts, MetaLootInstruction::Initialize),
+        MetaLootProgramError::IncorrectSystemProgram,
+    );
+}
+
+#[test]
+fn initialize_rejects_mint_not_owned_by_token_program() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.initialize_accounts();
+    accounts[3].owner = Pubkey::new_unique();
+    assert_error(
+        escrow.run(&mut accounts, MetaLootInstruction::Initialize),
+        MetaLootProgramError::NotMint,
+    );
+}
+
+#[test]
+fn initialize_rejects_signer_other_than_upgrade_authority() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.initialize_accounts();
+    accounts[0] = TestAccount::signer(Pubkey::new_unique());
+    assert_error(
+        escrow.run(&mut accounts, MetaLootInstruction::Initialize),
+        MetaLootProgramError::NotUpgradeAuthority,
+    );
+}
+
+#[test]
+fn initialize_rejects_immutable_program() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.initialize_accounts();
+    accounts[6] = escrow.program_data_account(None);
+    assert_error(
+        escrow.run(&mut accounts, MetaLootInstruction::Initialize),
+        MetaLootProgramError::NotUpgradeAuthority,
+    );
+}
+
+#[test]
+fn initialize_rejects_program_data_of_other_program() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.initialize_accounts();
+    accounts[6].key = find_program_data_address(&Pubkey::new_unique());
+    assert_error(
+        escrow.run(&mut accounts, MetaLootInstruction::Initialize),
+        MetaLootProgramError::InvalidProgramData,
+    );
+}
+
+#[test]
+fn initialize_rejects_program_data_not_owned_by_loader() {
+    let escrow = Escrow::new();
+    let mut accounts = escrow.initialize_accounts();
+    accounts[6].owner = Pubkey::new_unique();
+    assert_error(
+        escrow.run(&mut accounts, MetaLootInstruction::Initialize),
+        MetaLootProgramError::InvalidProgramData,
+    );
+}
@@ -0,0 +1,257 @@
+use crate::{
+    cluster::ClusterConfig, error::MetaLootError, find_master_edition_pda, find_metadata_pda,
+    inspect, tx, Action, TxOptions, TxOutcome,
+};
+use mpl_token_metadata::{
+    instructions::{
+        UpdateMetadataAccountV2, UpdateMetadataAccountV2InstructionArgs, UpdateV1,
+        UpdateV1InstructionArgs,
+    },
+    types::{CollectionDetailsToggle, CollectionToggle, RuleSetToggle, TokenStandard, UsesToggle},
+};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use solana_sdk::{pubkey::Pubkey, signature::Signature, signer::Signer};
+use solana_system_interface::instruction as system_instruction;
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Where the CLI appends authority changes unless told otherwise.
+pub const DEFAULT_AUDIT_LOG: &str = "authority-audit.jsonl";
+
+/// One line of the audit log, written for every update authority change
+/// that was sent.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorityAuditEntry {
+    /// Unix time the change was confirmed, in seconds.
+    pub timestamp: u64,
+    pub rpc_url: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub mint: Pubkey,
+    #[serde_as(as = "DisplayFromStr")]
+    pub metadata_pda: Pubkey,
+    #[serde_as(as = "DisplayFromStr")]
+    pub previous_authority: Pubkey,
+    #[serde_as(as = "DisplayFromStr")]
+    pub new_authority: Pubkey,
+    /// The test transaction the new authority co-signed, if one was required.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub cosign_signature: Option<Signature>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub signature: Signature,
+}
+
+/// Transactions sent by `transfer_update_authority`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthorityTransfer {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    /// The co-signed test transaction, when a co-signer was given.
+    pub cosign: Option<TxOutcome>,
+    pub outcome: TxOutcome,
+}
+
+/// Checks that `new_authority` can take over the metadata of `mint` from
+/// `current`: it must differ from it, must not be a well-known program or one
+/// of the NFT's own accounts, and must not be an executable account. An
+/// off-curve key (a multisig vault or program PDA) is allowed, but only its
+/// program can sign for it from then on.
+pub fn validate_new_authority(
+    cluster: &ClusterConfig,
+    mint: &Pubkey,
+    current: &Pubkey,
+    new_authority: &Pubkey,
+) -> Result<(), MetaLootError> {
+    let invalid = |reason: &str| {
+        Err(MetaLootError::InvalidInput(format!(
+            "new update authority {} {}",
+            new_authority, reason
+        )))
+    };
+    if new_authority == current {
+        return invalid("is already the update authority");
+    }
+    if *new_authority == Pubkey::default() {
+        return invalid("is the system program; the metadata would be locked for good");
+    }
+    if [
+        mpl_token_metadata::ID,
+        spl_token::ID,
+        spl_associated_token_account::ID,
+    ]
+    .contains(new_authority)
+    {
+        return invalid("is one of the token programs");
+    }
+    if [
+        *mint,
+        find_metadata_pda(mint),
+        find_master_edition_pda(mint),
+    ]
+    .contains(new_authority)
+    {
+        return invalid("is an account of the NFT itself");
+    }
+    let client = cluster.client();
+    let account = client
+        .get_account_with_commitment(new_authority, client.commitment())?
+        .value;
+    if account.is_some_and(|account| account.executable) {
+        return invalid("is a program; use a PDA of it instead");
+    }
+    if !new_authority.is_on_curve() {
+        println!(
+            "Note: {} is off-curve (a PDA); only its program can sign metadata changes.",
+            new_authority
+        );
+    }
+    Ok(())
+}
+
+/// Hands the update authority of `mint` from `authority` to `new_authority`.
+///
+/// When `cosigner` is given it must be the new authority: it first signs a
+/// zero-lamport test transaction, so a mistyped key or a keypair nobody holds
+/// is caught before anything changes. Every change that is sent is appended
+/// to `audit_log` as a JSON line.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_update_authority(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    authority: &dyn Signer,
+    mint: Pubkey,
+    new_authority: Pubkey,
+    cosigner: Option<&dyn Signer>,
+    audit_log: Option<&Path>,
+    options: &TxOptions,
+) -> Result<AuthorityTransfer, MetaLootError> {
+    let metadata_pda = find_metadata_pda(&mint);
+    let current = inspect::fetch_decoded_metadata(cluster, &metadata_pda)?;
+    if current.update_authority != authority.pubkey() {
+        return Err(MetaLootError::InvalidInput(format!(
+            "{} is not the update authority of {}; it is {}",
+            authority.pubkey(),
+            mint,
+            current.update_authority
+        )));
+    }
+    validate_new_authority(cluster, &mint, &current.update_authority, &new_authority)?;
+
+    let cosign = match cosigner {
+        Some(cosigner) if cosigner.pubkey() != new_authority => {
+            return Err(MetaLootError::InvalidInput(format!(
+                "co-signer {} is not the new update authority {}",
+                cosigner.pubkey(),
+                new_authority
+            )))
+        }
+        Some(cosigner) => Some(tx::submit(
+            cluster,
+            payer,
+            &[cosigner],
+            &[system_instruction::transfer(
+                &new_authority,
+                &new_authority,
+                0,
+            )],
+            options,
+            "New update authority co-signed a test transaction",
+        )?),
+        None => None,
+    };
+
+    let instruction = match current.token_standard {
+        // Programmable NFTs reject the legacy instruction
+        Some(TokenStandard::ProgrammableNonFungible)
+        | Some(TokenStandard::ProgrammableNonFungibleEdition) => UpdateV1 {
+            authority: authority.pubkey(),
+            delegate_record: None,
+            token: None,
+            mint,
+            metadata: metadata_pda,
+            edition: Some(find_master_edition_pda(&mint)),
+            payer: payer.pubkey(),
+            system_program: solana_sdk::system_program::ID,
+            sysvar_instructions: solana_sdk::sysvar::instructions::ID,
+            authorization_rules_program: None,
+            authorization_rules: None,
+        }
+        .instruction(UpdateV1InstructionArgs {
+            new_update_authority: Some(new_authority),
+            data: None,
+            primary_sale_happened: None,
+            is_mutable: None,
+            collection: CollectionToggle::None,
+            collection_details: CollectionDetailsToggle::None,
+            uses: UsesToggle::None,
+            rule_set: RuleSetToggle::None,
+            authorization_data: None,
+        }),
+        _ => UpdateMetadataAccountV2 {
+            metadata: metadata_pda,
+            update_authority: authority.pubkey(),
+        }
+        .instruction(UpdateMetadataAccountV2InstructionArgs {
+            data: None,
+            new_update_authority: Some(new_authority),
+            primary_sale_happened: None,
+            is_mutable: None,
+        }),
+    };
+
+    let outcome = tx::submit(
+        cluster,
+        payer,
+        &[authority],
+        &[instruction],
+        options,
+        Action::new("Update authority transferred").irreversible(format!(
+            "only {} can change the metadata or hand the authority on",
+            new_authority
+        )),
+    )?;
+
+    if let (Some(signature), Some(path)) = (outcome.signature(), audit_log) {
+        let entry = AuthorityAuditEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            rpc_url: cluster.rpc_url.clone(),
+            mint,
+            metadata_pda,
+            previous_authority: current.update_authority,
+            new_authority,
+            cosign_signature: cosign.as_ref().and_then(TxOutcome::signature).copied(),
+            signature: *signature,
+        };
+        append_audit_entry(path, &entry)?;
+        println!("Recorded in {}", path.display());
+    }
+
+    Ok(AuthorityTransfer {
+        previous_authority: current.update_authority,
+        new_authority,
+        cosign,
+        outcome,
+    })
+}
+
+/// Appends `entry` to the JSON-lines log at `path`, creating it if needed.
+pub fn append_audit_entry(path: &Path, entry: &AuthorityAuditEntry) -> Result<(), MetaLootError> {
+    append_json_line(path, entry)
+}
+
+/// Appends `entry` as one JSON line to `path`, creating the file if needed.
+pub(crate) fn append_json_line<T: Serialize>(path: &Path, entry: &T) -> Result<(), MetaLootError> {
+    let line = serde_json::to_string(entry).map_err(|err| {
+        MetaLootError::InvalidInput(format!("could not encode log entry: {}", err))
+    })?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
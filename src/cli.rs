@@ -0,0 +1,762 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use my_project::{cluster, ClusterConfig, MetaLootError, OnExisting, SignerSource, TxOptions};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+
+/// Manage MetaLoot token metadata, collections and editions.
+///
+/// Every input can be passed as a flag; anything left out is asked for
+/// interactively, and running without a subcommand opens the menu.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    /// Cluster to send transactions to
+    #[arg(long, short = 'n', value_enum, global = true)]
+    pub network: Option<Network>,
+
+    /// Custom RPC endpoint, e.g. a private provider or a local validator
+    #[arg(long, short = 'u', global = true, conflicts_with = "network")]
+    pub url: Option<String>,
+
+    /// Websocket endpoint used to confirm transactions [default: derived from
+    /// the RPC URL]
+    #[arg(long, global = true)]
+    pub ws_url: Option<String>,
+
+    /// Commitment level for reads and confirmations
+    #[arg(long, value_enum, global = true, default_value_t = Commitment::Confirmed)]
+    pub commitment: Commitment,
+
+    /// Seconds before an RPC or off-chain request gives up
+    #[arg(long, global = true, default_value_t = cluster::DEFAULT_TIMEOUT.as_secs())]
+    pub timeout: u64,
+
+    /// Seconds to wait for a sent transaction to reach the commitment level
+    #[arg(long, global = true, default_value_t = cluster::DEFAULT_CONFIRM_TIMEOUT.as_secs())]
+    pub confirm_timeout: u64,
+
+    /// Payer keypair: a file path, `config` (Solana CLI default), `env:<VAR>`,
+    /// `seed-phrase` or `stdin` [default: $METALOOT_KEYPAIR, then `config`]
+    #[arg(long, short = 'k', global = true)]
+    pub keypair: Option<SignerSource>,
+
+    /// BIP44 `<account>/<change>` path used with `--keypair seed-phrase`
+    #[arg(long, global = true, requires = "keypair")]
+    pub derivation_path: Option<String>,
+
+    /// Sign and simulate transactions, reporting logs, compute units, fee and
+    /// balance changes without sending anything
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Send mainnet transactions without typing the confirmation; the summary
+    /// of accounts, cost and irreversible effects is still printed
+    #[arg(long, global = true)]
+    pub yes_mainnet: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// The cluster from `--url` or `--network`, asking for one if neither is given.
+    pub fn cluster(&self) -> io::Result<ClusterConfig> {
+        let rpc_url = match (&self.url, self.network) {
+            (Some(url), _) => url.clone(),
+            (None, Some(network)) => network.rpc_url().to_string(),
+            (None, None) => prompt_rpc_url()?,
+        };
+        Ok(ClusterConfig::new(rpc_url)
+            .with_ws_url(self.ws_url.clone())
+            .with_commitment(self.commitment.config())
+            .with_timeout(Duration::from_secs(self.timeout))
+            .with_confirm_timeout(Duration::from_secs(self.confirm_timeout)))
+    }
+
+    pub fn tx_options(&self) -> TxOptions {
+        TxOptions {
+            dry_run: self.dry_run,
+            yes_mainnet: self.yes_mainnet,
+        }
+    }
+
+    pub fn signer_source(&self) -> Result<SignerSource, MetaLootError> {
+        self.keypair
+            .clone()
+            .unwrap_or_default()
+            .with_derivation_path(self.derivation_path.as_deref())
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Devnet,
+    Testnet,
+    Mainnet,
+    /// A local `solana-test-validator`
+    Localnet,
+}
+
+impl Network {
+    pub fn rpc_url(self) -> &'static str {
+        match self {
+            Network::Devnet => cluster::DEVNET_URL,
+            Network::Testnet => cluster::TESTNET_URL,
+            Network::Mainnet => cluster::MAINNET_URL,
+            Network::Localnet => cluster::LOCALNET_URL,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    pub fn config(self) -> CommitmentConfig {
+        match self {
+            Commitment::Processed => CommitmentConfig::processed(),
+            Commitment::Confirmed => CommitmentConfig::confirmed(),
+            Commitment::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Create or update the metadata account of a mint
+    #[command(subcommand)]
+    Metadata(MetadataCommand),
+    /// Create collection parents and verify items into them
+    #[command(subcommand)]
+    Collection(CollectionCommand),
+    /// Master edition accounts
+    #[command(subcommand)]
+    MasterEdition(MasterEditionCommand),
+    /// Fungible tokens
+    #[command(subcommand)]
+    Token(TokenCommand),
+    /// Non-fungible tokens
+    #[command(subcommand)]
+    Nft(NftCommand),
+    /// Drive a deployed reward escrow program
+    #[command(subcommand)]
+    Escrow(EscrowCommand),
+    /// Inspect the payer keypair
+    #[command(subcommand)]
+    Keypair(KeypairCommand),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MetadataCommand {
+    /// Create a metadata account from a spec file
+    Create(CreateArgs),
+    /// Update the fields of an existing metadata account that differ from a spec file
+    Update(UpdateArgs),
+    /// Decode the on-chain metadata, edition, collection and off-chain JSON
+    Show(ShowArgs),
+    /// Hand the update authority to another key, e.g. a team multisig
+    TransferAuthority(TransferAuthorityArgs),
+    /// Check the off-chain JSON and image, then make the metadata immutable for good
+    Freeze(FreezeArgs),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CollectionCommand {
+    /// Create sized-collection metadata from a spec file
+    Create(CreateArgs),
+    /// Verify an NFT into a collection, picking the instruction the collection needs
+    Verify(VerifyArgs),
+    /// Remove an NFT from the collection it is verified into
+    Unverify(UnverifyArgs),
+    /// Move an NFT into another collection in one transaction
+    Move(MoveArgs),
+    /// Approve, revoke and list collection authority delegates
+    #[command(subcommand)]
+    Delegate(DelegateCommand),
+    /// Count the verified items and compare them with the recorded collection size
+    Size(SizeArgs),
+    /// Make the metadata of every verified item immutable for good
+    Freeze(CollectionFreezeArgs),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DelegateCommand {
+    /// Let a key verify and unverify items of a collection
+    Approve(DelegateArgs),
+    /// Withdraw a delegate's collection authority
+    Revoke(DelegateArgs),
+    /// List the delegates approved for a collection
+    List(CollectionArgs),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MasterEditionCommand {
+    /// Create the master edition account of a mint
+    Create(MasterEditionArgs),
+    /// Mint a numbered print edition from a master edition the payer holds
+    Print(PrintArgs),
+    /// List the edition numbers printed from a master edition
+    Editions(MintArgs),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TokenCommand {
+    /// Create a mint, its token account, the initial supply and metadata in one transaction
+    Create(TokenCreateArgs),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum NftCommand {
+    /// Create a mint, token account, metadata and master edition for one NFT
+    Mint(NftMintArgs),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EscrowCommand {
+    /// Create the escrow token account and config for a mint; the admin must
+    /// be the program's upgrade authority
+    Init(EscrowInitArgs),
+    /// Move tokens from your associated token account into escrow
+    Deposit(EscrowAmountArgs),
+    /// Pay tokens from escrow back to a wallet, even while paused
+    Withdraw(EscrowWithdrawArgs),
+    /// Pay a reward from escrow to a user, once per claim id
+    Claim(EscrowClaimArgs),
+    /// Tell whether a claim id has been paid to a user
+    Claimed(EscrowClaimedArgs),
+    /// Hand the admin role to another key
+    SetAdmin(EscrowSetAdminArgs),
+    /// Refuse deposits and claims until resumed
+    Pause(EscrowSignerArgs),
+    /// Accept deposits and claims again
+    Resume(EscrowSignerArgs),
+    /// Empty the escrow into a wallet and close its accounts
+    Close(EscrowCloseArgs),
+    /// Print the admin, mint, escrow account and pause state
+    Show(EscrowProgramArgs),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum KeypairCommand {
+    /// Print the public key of the resolved payer
+    Show(KeypairShowArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct KeypairShowArgs {
+    /// Also print the secret key bytes
+    #[arg(long)]
+    pub show_secret: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct MintArgs {
+    /// Mint address of the token
+    #[arg(long, short = 'm')]
+    pub mint: Option<Pubkey>,
+}
+
+#[derive(Args, Debug)]
+pub struct MintSpecArgs {
+    #[command(flatten)]
+    pub mint: MintArgs,
+    /// Metadata spec file (TOML or JSON)
+    #[arg(long, short = 's')]
+    pub spec: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct CreateArgs {
+    #[command(flatten)]
+    pub mint_spec: MintSpecArgs,
+    /// Update the metadata instead when it already exists and differs from the spec
+    #[arg(long)]
+    pub update_existing: bool,
+}
+
+impl CreateArgs {
+    pub fn on_existing(&self) -> OnExisting {
+        if self.update_existing {
+            OnExisting::Update
+        } else {
+            OnExisting::Skip
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct UpdateArgs {
+    #[command(flatten)]
+    pub mint_spec: MintSpecArgs,
+    /// Only print the diff against the on-chain metadata
+    #[arg(long)]
+    pub preview: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct TokenCreateArgs {
+    /// Metadata spec file (TOML or JSON)
+    #[arg(long, short = 's')]
+    pub spec: Option<PathBuf>,
+    /// Decimal places of the token
+    #[arg(long, default_value_t = 9)]
+    pub decimals: u8,
+    /// Whole tokens minted to the payer's token account
+    #[arg(long, default_value_t = 0)]
+    pub supply: u64,
+    /// Keypair of the new mint, in any `--keypair` form [default: a fresh keypair]
+    #[arg(long)]
+    pub mint_keypair: Option<SignerSource>,
+}
+
+impl TokenCreateArgs {
+    pub fn spec(&self) -> Result<PathBuf, Box<dyn Error>> {
+        match &self.spec {
+            Some(spec) => Ok(spec.clone()),
+            None => prompt_spec(),
+        }
+    }
+
+    /// The supply in base units.
+    pub fn base_units(&self) -> Result<u64, Box<dyn Error>> {
+        10u64
+            .checked_pow(self.decimals.into())
+            .and_then(|unit| unit.checked_mul(self.supply))
+            .ok_or_else(|| {
+                format!(
+                    "a supply of {} with {} decimals does not fit in a u64",
+                    self.supply, self.decimals
+                )
+                .into()
+            })
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct MasterEditionArgs {
+    #[command(flatten)]
+    pub mint: MintArgs,
+    /// Most prints the master edition allows [default: unlimited]
+    #[arg(long)]
+    pub max_supply: Option<u64>,
+}
+
+#[derive(Args, Debug)]
+pub struct PrintArgs {
+    /// Mint of the master edition
+    #[command(flatten)]
+    pub master: MintArgs,
+    /// Edition number to print [default: the lowest free one]
+    #[arg(long)]
+    pub edition: Option<u64>,
+    /// Wallet receiving the print [default: the payer]
+    #[arg(long)]
+    pub owner: Option<Pubkey>,
+    /// Keypair of the new mint, in any `--keypair` form [default: a fresh keypair]
+    #[arg(long)]
+    pub mint_keypair: Option<SignerSource>,
+}
+
+#[derive(Args, Debug)]
+pub struct NftMintArgs {
+    /// Metadata spec file (TOML or JSON)
+    #[arg(long, short = 's')]
+    pub spec: Option<PathBuf>,
+    /// Wallet receiving the NFT [default: the payer]
+    #[arg(long)]
+    pub owner: Option<Pubkey>,
+    /// Most prints the master edition allows [default: unlimited]
+    #[arg(long)]
+    pub max_supply: Option<u64>,
+    /// Verify the NFT into the collection named in the spec
+    #[arg(long)]
+    pub verify_collection: bool,
+    /// Keypair of the new mint, in any `--keypair` form [default: a fresh keypair]
+    #[arg(long)]
+    pub mint_keypair: Option<SignerSource>,
+}
+
+impl NftMintArgs {
+    pub fn spec(&self) -> Result<PathBuf, Box<dyn Error>> {
+        match &self.spec {
+            Some(spec) => Ok(spec.clone()),
+            None => prompt_spec(),
+        }
+    }
+}
+
+/// Who signs for the collection when verifying or unverifying items.
+#[derive(Args, Debug)]
+pub struct AuthorityArgs {
+    /// Collection update authority or approved delegate, in any `--keypair`
+    /// form [default: the payer]
+    #[arg(long)]
+    pub authority: Option<SignerSource>,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// Mint of the item to verify
+    #[command(flatten)]
+    pub mint: MintArgs,
+    /// Mint of the collection parent [default: the collection the item names]
+    #[arg(long, short = 'c')]
+    pub collection: Option<Pubkey>,
+    #[command(flatten)]
+    pub authority: AuthorityArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct UnverifyArgs {
+    /// Mint of the item to unverify
+    #[command(flatten)]
+    pub mint: MintArgs,
+    #[command(flatten)]
+    pub authority: AuthorityArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct CollectionArgs {
+    /// Mint of the collection parent
+    #[arg(long, short = 'c')]
+    pub collection: Option<Pubkey>,
+}
+
+impl CollectionArgs {
+    pub fn collection(&self) -> Result<Pubkey, Box<dyn Error>> {
+        match self.collection {
+            Some(collection) => Ok(collection),
+            None => prompt_collection(),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct SizeArgs {
+    #[command(flatten)]
+    pub collection: CollectionArgs,
+    /// Update authority whose metadata accounts are scanned for items
+    /// [default: the collection's update authority]
+    #[arg(long)]
+    pub update_authority: Option<Pubkey>,
+    /// Send SetCollectionSize when an unsized collection has no size yet
+    #[arg(long)]
+    pub fix: bool,
+    #[command(flatten)]
+    pub authority: AuthorityArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct CollectionFreezeArgs {
+    #[command(flatten)]
+    pub collection: CollectionArgs,
+    /// Update authority whose metadata accounts are scanned for items
+    /// [default: the collection's update authority]
+    #[arg(long)]
+    pub update_authority: Option<Pubkey>,
+    /// Update authority of the items, in any `--keypair` form [default: the payer]
+    #[arg(long)]
+    pub authority: Option<SignerSource>,
+    /// Freeze without asking for confirmation
+    #[arg(long)]
+    pub yes: bool,
+    /// JSON-lines file the hashes of frozen metadata are appended to
+    #[arg(long, default_value = my_project::freeze::DEFAULT_FREEZE_RECORD)]
+    pub record: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct DelegateArgs {
+    #[command(flatten)]
+    pub collection: CollectionArgs,
+    /// Key being given or losing collection authority
+    #[arg(long, short = 'd')]
+    pub delegate: Option<Pubkey>,
+}
+
+impl DelegateArgs {
+    pub fn delegate(&self) -> Result<Pubkey, Box<dyn Error>> {
+        match self.delegate {
+            Some(delegate) => Ok(delegate),
+            None => Ok(Pubkey::from_str(&prompt(
+                "Please enter the delegate address:",
+            )?)?),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct MoveArgs {
+    /// Mint of the item to move
+    #[command(flatten)]
+    pub mint: MintArgs,
+    /// Mint of the new collection parent
+    #[command(flatten)]
+    pub collection: CollectionArgs,
+    #[command(flatten)]
+    pub authority: AuthorityArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ShowArgs {
+    #[command(flatten)]
+    pub mint: MintArgs,
+    /// Print JSON instead of a table
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct TransferAuthorityArgs {
+    #[command(flatten)]
+    pub mint: MintArgs,
+    /// Key taking over the update authority
+    #[arg(long)]
+    pub new_authority: Pubkey,
+    /// Current update authority, in any `--keypair` form [default: the payer]
+    #[arg(long)]
+    pub authority: Option<SignerSource>,
+    /// The new authority's keypair, in any `--keypair` form; it co-signs a
+    /// test transaction before the handover
+    #[arg(long)]
+    pub cosign: Option<SignerSource>,
+    /// JSON-lines file the change is appended to
+    #[arg(long, default_value = my_project::authority::DEFAULT_AUDIT_LOG)]
+    pub audit_log: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct EscrowProgramArgs {
+    /// Address the escrow program is deployed at
+    #[arg(long)]
+    pub program_id: Pubkey,
+}
+
+#[derive(Args, Debug)]
+pub struct EscrowSignerArgs {
+    #[command(flatten)]
+    pub program: EscrowProgramArgs,
+    /// Escrow admin, or the depositor for `deposit`, in any `--keypair` form
+    /// [default: the payer]
+    #[arg(long)]
+    pub authority: Option<SignerSource>,
+}
+
+#[derive(Args, Debug)]
+pub struct EscrowInitArgs {
+    #[command(flatten)]
+    pub signer: EscrowSignerArgs,
+    /// Mint of the tokens the escrow holds
+    #[arg(long, short = 'm')]
+    pub mint: Pubkey,
+}
+
+#[derive(Args, Debug)]
+pub struct EscrowAmountArgs {
+    #[command(flatten)]
+    pub signer: EscrowSignerArgs,
+    /// Amount in base units
+    #[arg(long)]
+    pub amount: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct EscrowWithdrawArgs {
+    #[command(flatten)]
+    pub signer: EscrowSignerArgs,
+    /// Amount in base units
+    #[arg(long)]
+    pub amount: u64,
+    /// Wallet receiving the tokens [default: the payer]
+    #[arg(long)]
+    pub wallet: Option<Pubkey>,
+}
+
+#[derive(Args, Debug)]
+pub struct EscrowCloseArgs {
+    #[command(flatten)]
+    pub signer: EscrowSignerArgs,
+    /// Wallet receiving what is left in escrow [default: the payer]
+    #[arg(long)]
+    pub wallet: Option<Pubkey>,
+}
+
+#[derive(Args, Debug)]
+pub struct EscrowClaimArgs {
+    #[command(flatten)]
+    pub signer: EscrowSignerArgs,
+    /// Amount in base units
+    #[arg(long)]
+    pub amount: u64,
+    /// Wallet of the user receiving the reward
+    #[arg(long)]
+    pub user: Pubkey,
+    /// Id of the claim; each is paid at most once per user
+    #[arg(long)]
+    pub claim_id: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct EscrowClaimedArgs {
+    #[command(flatten)]
+    pub program: EscrowProgramArgs,
+    /// Wallet of the user
+    #[arg(long)]
+    pub user: Pubkey,
+    /// Id of the claim
+    #[arg(long)]
+    pub claim_id: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct EscrowSetAdminArgs {
+    #[command(flatten)]
+    pub signer: EscrowSignerArgs,
+    /// Key taking over the admin role
+    #[arg(long)]
+    pub new_admin: Pubkey,
+}
+
+#[derive(Args, Debug)]
+pub struct FreezeArgs {
+    #[command(flatten)]
+    pub mint: MintArgs,
+    /// Update authority, in any `--keypair` form [default: the payer]
+    #[arg(long)]
+    pub authority: Option<SignerSource>,
+    /// Freeze without asking for confirmation
+    #[arg(long)]
+    pub yes: bool,
+    /// JSON-lines file the hashes of frozen metadata are appended to
+    #[arg(long, default_value = my_project::freeze::DEFAULT_FREEZE_RECORD)]
+    pub record: PathBuf,
+}
+
+impl MintArgs {
+    pub fn mint(&self) -> Result<Pubkey, Box<dyn Error>> {
+        match self.mint {
+            Some(mint) => Ok(mint),
+            None => prompt_mint(),
+        }
+    }
+}
+
+impl MintSpecArgs {
+    pub fn spec(&self) -> Result<PathBuf, Box<dyn Error>> {
+        match &self.spec {
+            Some(spec) => Ok(spec.clone()),
+            None => prompt_spec(),
+        }
+    }
+}
+
+fn prompt(question: &str) -> io::Result<String> {
+    println!("{}", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().to_string())
+}
+
+fn prompt_mint() -> Result<Pubkey, Box<dyn Error>> {
+    Ok(Pubkey::from_str(&prompt(
+        "Please enter the mint address:",
+    )?)?)
+}
+
+pub fn prompt_collection() -> Result<Pubkey, Box<dyn Error>> {
+    Ok(Pubkey::from_str(&prompt(
+        "Please enter the collection mint address:",
+    )?)?)
+}
+
+fn prompt_spec() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(PathBuf::from(prompt(
+        "Please enter the metadata spec file:",
+    )?))
+}
+
+fn prompt_rpc_url() -> io::Result<String> {
+    println!("Choose network:");
+    println!("1. Devnet");
+    println!("2. Testnet");
+    println!("3. Mainnet");
+    println!("4. Localnet ({})", cluster::LOCALNET_URL);
+    println!("5. Custom RPC URL");
+
+    loop {
+        match prompt("")?.as_str() {
+            "1" => return Ok(Network::Devnet.rpc_url().to_string()),
+            "2" => return Ok(Network::Testnet.rpc_url().to_string()),
+            "3" => return Ok(Network::Mainnet.rpc_url().to_string()),
+            "4" => return Ok(Network::Localnet.rpc_url().to_string()),
+            "5" => match prompt("RPC URL:")?.as_str() {
+                "" => println!("No URL given, choose again"),
+                url => return Ok(url.to_string()),
+            },
+            // Empty input, or a closed stdin: never guess a cluster.
+            "" => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "no network chosen",
+                ))
+            }
+            _ => println!("Invalid choice, enter a number from 1 to 5"),
+        }
+    }
+}
+
+/// Menu used when no subcommand is given; the mint is asked for right after
+/// the choice, and only by commands that need one.
+pub fn prompt_command() -> Result<Command, Box<dyn Error>> {
+    let mint = || prompt_mint().map(|mint| MintArgs { mint: Some(mint) });
+    let with_spec = |mint| MintSpecArgs { mint, spec: None };
+    let create = |mint| CreateArgs {
+        mint_spec: with_spec(mint),
+        update_existing: false,
+    };
+
+    println!("Choose operation:");
+    println!("1. Create Metadata");
+    println!("2. Update Metadata");
+    println!("3. Create Collection");
+    println!("4. Verify Collection Item");
+    println!("5. Create Master Edition");
+    println!("6. Show Keypair");
+    println!("7. Show Metadata");
+
+    let command = match prompt("")?.as_str() {
+        "1" => Command::Metadata(MetadataCommand::Create(create(mint()?))),
+        "2" => Command::Metadata(MetadataCommand::Update(UpdateArgs {
+            mint_spec: with_spec(mint()?),
+            preview: false,
+        })),
+        "3" => Command::Collection(CollectionCommand::Create(create(mint()?))),
+        "4" => Command::Collection(CollectionCommand::Verify(VerifyArgs {
+            mint: mint()?,
+            collection: None,
+            authority: AuthorityArgs { authority: None },
+        })),
+        "5" => Command::MasterEdition(MasterEditionCommand::Create(MasterEditionArgs {
+            mint: mint()?,
+            max_supply: None,
+        })),
+        "6" => Command::Keypair(KeypairCommand::Show(KeypairShowArgs { show_secret: false })),
+        "7" => Command::Metadata(MetadataCommand::Show(ShowArgs {
+            mint: mint()?,
+            json: false,
+        })),
+        choice => return Err(format!("Invalid choice: {}", choice).into()),
+    };
+    Ok(command)
+}
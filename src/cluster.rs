@@ -0,0 +1,177 @@
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::{fmt, sync::OnceLock, time::Duration};
+
+pub const DEVNET_URL: &str = "https://api.devnet.solana.com";
+pub const TESTNET_URL: &str = "https://api.testnet.solana.com";
+pub const MAINNET_URL: &str = "https://api.mainnet-beta.solana.com";
+pub const LOCALNET_URL: &str = "http://127.0.0.1:8899";
+
+/// Default time allowed for a single RPC or off-chain request.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Default time allowed for a sent transaction to reach the commitment level.
+pub const DEFAULT_CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Where and how to talk to a cluster. Every library function that reads from
+/// or sends to the chain takes one of these; the RPC client is built on first
+/// use and shared from then on.
+pub struct ClusterConfig {
+    pub rpc_url: String,
+    /// Websocket endpoint sent transactions are confirmed through; derived
+    /// from `rpc_url` when not set.
+    pub ws_url: Option<String>,
+    pub commitment: CommitmentConfig,
+    pub timeout: Duration,
+    pub confirm_timeout: Duration,
+    client: OnceLock<RpcClient>,
+}
+
+impl ClusterConfig {
+    pub fn new(rpc_url: impl Into<String>) -> Self {
+        ClusterConfig {
+            rpc_url: rpc_url.into(),
+            ws_url: None,
+            commitment: CommitmentConfig::confirmed(),
+            timeout: DEFAULT_TIMEOUT,
+            confirm_timeout: DEFAULT_CONFIRM_TIMEOUT,
+            client: OnceLock::new(),
+        }
+    }
+
+    pub fn devnet() -> Self {
+        Self::new(DEVNET_URL)
+    }
+
+    pub fn testnet() -> Self {
+        Self::new(TESTNET_URL)
+    }
+
+    pub fn mainnet() -> Self {
+        Self::new(MAINNET_URL)
+    }
+
+    /// A `solana-test-validator` on its default ports.
+    pub fn localnet() -> Self {
+        Self::new(LOCALNET_URL)
+    }
+
+    pub fn with_ws_url(mut self, ws_url: Option<String>) -> Self {
+        self.ws_url = ws_url;
+        self
+    }
+
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_confirm_timeout(mut self, confirm_timeout: Duration) -> Self {
+        self.confirm_timeout = confirm_timeout;
+        self
+    }
+
+    /// The websocket override, or the RPC URL with its scheme swapped. A local
+    /// `http` validator gets its explicit port bumped by one (8899 → 8900), as
+    /// the Solana CLI does; any other endpoint keeps its port.
+    pub fn ws_url(&self) -> String {
+        if let Some(ws_url) = &self.ws_url {
+            return ws_url.clone();
+        }
+        let (scheme, rest) = match self.rpc_url.split_once("://") {
+            Some(("https", rest)) => ("wss", rest),
+            Some((_, rest)) => ("ws", rest),
+            None => ("ws", self.rpc_url.as_str()),
+        };
+        let local = scheme == "ws" && ["localhost", "127.0.0.1"].contains(&host_name(rest));
+        let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        let host = match host.rsplit_once(':') {
+            Some((name, port)) if local => match port.parse::<u16>() {
+                Ok(port) => format!("{}:{}", name, port.saturating_add(1)),
+                Err(_) => host.to_string(),
+            },
+            _ => host.to_string(),
+        };
+        format!("{}://{}{}", scheme, host, path)
+    }
+
+    pub fn client(&self) -> &RpcClient {
+        self.client.get_or_init(|| {
+            RpcClient::new_with_timeouts_and_commitment(
+                self.rpc_url.clone(),
+                self.timeout,
+                self.commitment,
+                self.confirm_timeout,
+            )
+        })
+    }
+}
+
+/// Host of a URL with its scheme already stripped, without port or path.
+fn host_name(rest: &str) -> &str {
+    let authority = rest.split('/').next().unwrap_or(rest);
+    authority
+        .rsplit_once(':')
+        .map_or(authority, |(name, _)| name)
+}
+
+impl Clone for ClusterConfig {
+    fn clone(&self) -> Self {
+        ClusterConfig {
+            rpc_url: self.rpc_url.clone(),
+            ws_url: self.ws_url.clone(),
+            commitment: self.commitment,
+            timeout: self.timeout,
+            confirm_timeout: self.confirm_timeout,
+            client: OnceLock::new(),
+        }
+    }
+}
+
+impl fmt::Debug for ClusterConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClusterConfig")
+            .field("rpc_url", &self.rpc_url)
+            .field("ws_url", &self.ws_url())
+            .field("commitment", &self.commitment.commitment)
+            .field("timeout", &self.timeout)
+            .field("confirm_timeout", &self.confirm_timeout)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ws_url(rpc_url: &str) -> String {
+        ClusterConfig::new(rpc_url).ws_url()
+    }
+
+    #[test]
+    fn local_validator_port_is_bumped() {
+        assert_eq!(ws_url(LOCALNET_URL), "ws://127.0.0.1:8900");
+        assert_eq!(ws_url("http://localhost:8899"), "ws://localhost:8900");
+    }
+
+    #[test]
+    fn remote_endpoints_keep_their_port() {
+        assert_eq!(ws_url(DEVNET_URL), "wss://api.devnet.solana.com");
+        assert_eq!(
+            ws_url("https://rpc.example.com:8443/key"),
+            "wss://rpc.example.com:8443/key"
+        );
+        assert_eq!(ws_url("http://10.0.0.5:8899"), "ws://10.0.0.5:8899");
+    }
+
+    #[test]
+    fn override_wins() {
+        let cluster =
+            ClusterConfig::localnet().with_ws_url(Some("ws://127.0.0.1:9000".to_string()));
+        assert_eq!(cluster.ws_url(), "ws://127.0.0.1:9000");
+    }
+}
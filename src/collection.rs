@@ -0,0 +1,721 @@
+use crate::{
+    cluster::ClusterConfig, error::MetaLootError, find_master_edition_pda, find_metadata_pda,
+    inspect, tx, TxOptions, TxOutcome,
+};
+use mpl_token_metadata::{
+    accounts::{CollectionAuthorityRecord, Metadata},
+    instructions::{
+        ApproveCollectionAuthority, RevokeCollectionAuthority, SetAndVerifyCollection,
+        SetAndVerifySizedCollectionItem, UnverifyCollection, UnverifyCollectionV1,
+        UnverifySizedCollectionItem, UpdateV1, UpdateV1InstructionArgs, VerifyCollection,
+        VerifyCollectionV1, VerifySizedCollectionItem,
+    },
+    types::{
+        Collection, CollectionDetailsToggle, CollectionToggle, RuleSetToggle, TokenStandard,
+        UsesToggle,
+    },
+};
+use solana_client::{
+    rpc_client::GetConfirmedSignaturesForAddress2Config, rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signature, signer::Signer};
+use solana_transaction_status_client_types::UiTransactionEncoding;
+use std::{fmt, str::FromStr};
+
+/// Which Token Metadata instruction verifies an item into a collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyMethod {
+    /// Legacy unsized collection, item not pointing at it yet.
+    SetAndVerify,
+    /// Legacy unsized collection, item already pointing at it.
+    Verify,
+    /// Sized collection, item not pointing at it yet.
+    SetAndVerifySized,
+    /// Sized collection, item already pointing at it.
+    VerifySized,
+    /// Programmable items, which only accept the newer instruction.
+    VerifyCollectionV1,
+}
+
+impl fmt::Display for VerifyMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            VerifyMethod::SetAndVerify => "SetAndVerifyCollection",
+            VerifyMethod::Verify => "VerifyCollection",
+            VerifyMethod::SetAndVerifySized => "SetAndVerifySizedCollectionItem",
+            VerifyMethod::VerifySized => "VerifySizedCollectionItem",
+            VerifyMethod::VerifyCollectionV1 => "VerifyCollectionV1",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl VerifyMethod {
+    /// Picks the instruction from the collection's `collection_details` (sized
+    /// or not), the item's token standard and whether it already names the
+    /// collection.
+    pub fn for_item(item: &Metadata, collection: &Metadata) -> Self {
+        let points_at_collection = points_at(item, &collection.mint);
+        match (
+            is_programmable(item),
+            collection.collection_details.is_some(),
+            points_at_collection,
+        ) {
+            (true, _, _) => VerifyMethod::VerifyCollectionV1,
+            (false, true, true) => VerifyMethod::VerifySized,
+            (false, true, false) => VerifyMethod::SetAndVerifySized,
+            (false, false, true) => VerifyMethod::Verify,
+            (false, false, false) => VerifyMethod::SetAndVerify,
+        }
+    }
+
+    /// Whether the item's update authority has to sign as well.
+    pub fn sets_collection(self) -> bool {
+        matches!(
+            self,
+            VerifyMethod::SetAndVerify | VerifyMethod::SetAndVerifySized
+        )
+    }
+}
+
+/// Builds the instruction that verifies `item_mint` into `collection_mint`
+/// with `method`. Every PDA is derived from the two mints; `authority_record`
+/// is the collection authority record when `authority` is a delegate.
+pub fn verify_instruction(
+    method: VerifyMethod,
+    authority: &Pubkey,
+    authority_record: Option<Pubkey>,
+    payer: &Pubkey,
+    item_mint: &Pubkey,
+    item_update_authority: &Pubkey,
+    collection_mint: &Pubkey,
+) -> Instruction {
+    let metadata = find_metadata_pda(item_mint);
+    let collection = find_metadata_pda(collection_mint);
+    let collection_master_edition = find_master_edition_pda(collection_mint);
+    match method {
+        VerifyMethod::SetAndVerify => SetAndVerifyCollection {
+            metadata,
+            collection_authority: *authority,
+            payer: *payer,
+            update_authority: *item_update_authority,
+            collection_mint: *collection_mint,
+            collection,
+            collection_master_edition_account: collection_master_edition,
+            collection_authority_record: authority_record,
+        }
+        .instruction(),
+        VerifyMethod::Verify => VerifyCollection {
+            metadata,
+            collection_authority: *authority,
+            payer: *payer,
+            collection_mint: *collection_mint,
+            collection,
+            collection_master_edition_account: collection_master_edition,
+            collection_authority_record: authority_record,
+        }
+        .instruction(),
+        VerifyMethod::SetAndVerifySized => SetAndVerifySizedCollectionItem {
+            metadata,
+            collection_authority: *authority,
+            payer: *payer,
+            update_authority: *item_update_authority,
+            collection_mint: *collection_mint,
+            collection,
+            collection_master_edition_account: collection_master_edition,
+            collection_authority_record: authority_record,
+        }
+        .instruction(),
+        VerifyMethod::VerifySized => VerifySizedCollectionItem {
+            metadata,
+            collection_authority: *authority,
+            payer: *payer,
+            collection_mint: *collection_mint,
+            collection,
+            collection_master_edition_account: collection_master_edition,
+            collection_authority_record: authority_record,
+        }
+        .instruction(),
+        VerifyMethod::VerifyCollectionV1 => VerifyCollectionV1 {
+            authority: *authority,
+            delegate_record: authority_record,
+            metadata,
+            collection_mint: *collection_mint,
+            collection_metadata: Some(collection),
+            collection_master_edition: Some(collection_master_edition),
+            system_program: solana_sdk::system_program::ID,
+            sysvar_instructions: solana_sdk::sysvar::instructions::ID,
+        }
+        .instruction(),
+    }
+}
+
+/// Fetches and decodes the metadata of `item_mint` and `collection_mint` in
+/// one request.
+pub fn fetch_item_and_collection(
+    cluster: &ClusterConfig,
+    item_mint: &Pubkey,
+    collection_mint: &Pubkey,
+) -> Result<(Metadata, Metadata), MetaLootError> {
+    let item_pda = find_metadata_pda(item_mint);
+    let collection_pda = find_metadata_pda(collection_mint);
+    let accounts = cluster
+        .client()
+        .get_multiple_accounts(&[item_pda, collection_pda])?;
+    let decode = |pda: &Pubkey, account: &Option<_>| match account {
+        Some(solana_sdk::account::Account { data, .. }) => inspect::decode_metadata(pda, data),
+        None => Err(MetaLootError::AccountNotFound(*pda)),
+    };
+    Ok((
+        decode(&item_pda, &accounts[0])?,
+        decode(&collection_pda, &accounts[1])?,
+    ))
+}
+
+/// Verifies `item_mint` into `collection_mint`. `authority` is the
+/// collection's update authority or an approved delegate, and may be the
+/// payer. The instruction is picked by `VerifyMethod::for_item`; returns
+/// `None` when the item is already verified into this collection.
+pub fn verify_collection_item(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    authority: &dyn Signer,
+    item_mint: Pubkey,
+    collection_mint: Pubkey,
+    options: &TxOptions,
+) -> Result<Option<TxOutcome>, MetaLootError> {
+    let (item, collection) = fetch_item_and_collection(cluster, &item_mint, &collection_mint)?;
+    match &item.collection {
+        Some(current) if current.verified && current.key == collection_mint => {
+            println!("{} is already verified into {}", item_mint, collection_mint);
+            return Ok(None);
+        }
+        Some(current) if current.verified => {
+            return Err(MetaLootError::InvalidInput(format!(
+                "{} is verified into {}; unverify it first",
+                item_mint, current.key
+            )));
+        }
+        _ => {}
+    }
+    let authority_record = collection_authority_record(cluster, &collection, &authority.pubkey())?;
+
+    let method = VerifyMethod::for_item(&item, &collection);
+    if method == VerifyMethod::VerifyCollectionV1 && !points_at(&item, &collection_mint) {
+        return Err(MetaLootError::InvalidInput(format!(
+            "programmable item {} must name collection {} before it can be verified; update its metadata first",
+            item_mint, collection_mint
+        )));
+    }
+    if method.sets_collection() && !signs(&item.update_authority, payer, authority) {
+        return Err(MetaLootError::InvalidInput(format!(
+            "setting the collection of {} needs its update authority {} to sign",
+            item_mint, item.update_authority
+        )));
+    }
+    println!("Verifying with {}", method);
+
+    let instruction = verify_instruction(
+        method,
+        &authority.pubkey(),
+        authority_record,
+        &payer.pubkey(),
+        &item_mint,
+        &item.update_authority,
+        &collection_mint,
+    );
+    tx::submit(
+        cluster,
+        payer,
+        &[authority],
+        &[instruction],
+        options,
+        "Collection verified for NFT",
+    )
+    .map(Some)
+}
+
+/// Builds the instruction that unverifies the item from `collection`, matching
+/// how it was verified: sized, legacy, or the newer one for programmable items.
+pub fn unverify_instruction(
+    authority: &Pubkey,
+    authority_record: Option<Pubkey>,
+    payer: &Pubkey,
+    item: &Metadata,
+    collection: &Metadata,
+) -> Instruction {
+    let metadata = find_metadata_pda(&item.mint);
+    let collection_mint = collection.mint;
+    let collection_metadata = find_metadata_pda(&collection_mint);
+    let collection_master_edition = find_master_edition_pda(&collection_mint);
+    if is_programmable(item) {
+        UnverifyCollectionV1 {
+            authority: *authority,
+            delegate_record: authority_record,
+            metadata,
+            collection_mint,
+            collection_metadata: Some(collection_metadata),
+            system_program: solana_sdk::system_program::ID,
+            sysvar_instructions: solana_sdk::sysvar::instructions::ID,
+        }
+        .instruction()
+    } else if collection.collection_details.is_some() {
+        UnverifySizedCollectionItem {
+            metadata,
+            collection_authority: *authority,
+            payer: *payer,
+            collection_mint,
+            collection: collection_metadata,
+            collection_master_edition_account: collection_master_edition,
+            collection_authority_record: authority_record,
+        }
+        .instruction()
+    } else {
+        UnverifyCollection {
+            metadata,
+            collection_authority: *authority,
+            collection_mint,
+            collection: collection_metadata,
+            collection_master_edition_account: collection_master_edition,
+            collection_authority_record: authority_record,
+        }
+        .instruction()
+    }
+}
+
+/// Removes `item_mint` from the collection it is verified into. `authority`
+/// is that collection's update authority or an approved delegate. Returns
+/// `None` when the item is not verified into any collection.
+pub fn unverify_collection_item(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    authority: &dyn Signer,
+    item_mint: Pubkey,
+    options: &TxOptions,
+) -> Result<Option<TxOutcome>, MetaLootError> {
+    let item = inspect::fetch_decoded_metadata(cluster, &find_metadata_pda(&item_mint))?;
+    let collection_mint = match &item.collection {
+        Some(collection) if collection.verified => collection.key,
+        _ => {
+            println!("{} is not verified into a collection", item_mint);
+            return Ok(None);
+        }
+    };
+    let collection =
+        inspect::fetch_decoded_metadata(cluster, &find_metadata_pda(&collection_mint))?;
+    let authority_record = collection_authority_record(cluster, &collection, &authority.pubkey())?;
+
+    let instruction = unverify_instruction(
+        &authority.pubkey(),
+        authority_record,
+        &payer.pubkey(),
+        &item,
+        &collection,
+    );
+    tx::submit(
+        cluster,
+        payer,
+        &[authority],
+        &[instruction],
+        options,
+        "Collection unverified for NFT",
+    )
+    .map(Some)
+}
+
+/// Moves `item_mint` into `new_collection_mint` in one transaction: it is
+/// unverified from its current collection (if verified), pointed at the new
+/// one and verified there. `authority` must be the update authority or an
+/// approved delegate of both collections, and the item's update authority
+/// must be either it or the payer. Returns `None` when the item is already
+/// verified into the new collection.
+pub fn move_collection_item(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    authority: &dyn Signer,
+    item_mint: Pubkey,
+    new_collection_mint: Pubkey,
+    options: &TxOptions,
+) -> Result<Option<TxOutcome>, MetaLootError> {
+    let payer_key = payer.pubkey();
+    let authority_key = authority.pubkey();
+    let (item, new_collection) =
+        fetch_item_and_collection(cluster, &item_mint, &new_collection_mint)?;
+    let new_record = collection_authority_record(cluster, &new_collection, &authority_key)?;
+    if !signs(&item.update_authority, payer, authority) {
+        return Err(MetaLootError::InvalidInput(format!(
+            "moving {} needs its update authority {} to sign",
+            item_mint, item.update_authority
+        )));
+    }
+
+    let mut instructions = Vec::new();
+    match &item.collection {
+        Some(current) if current.verified && current.key == new_collection_mint => {
+            println!(
+                "{} is already verified into {}",
+                item_mint, new_collection_mint
+            );
+            return Ok(None);
+        }
+        Some(current) if current.verified => {
+            let old_collection =
+                inspect::fetch_decoded_metadata(cluster, &find_metadata_pda(&current.key))?;
+            let old_record = collection_authority_record(cluster, &old_collection, &authority_key)?;
+            println!("Unverifying from {}", current.key);
+            instructions.push(unverify_instruction(
+                &authority_key,
+                old_record,
+                &payer_key,
+                &item,
+                &old_collection,
+            ));
+        }
+        _ => {}
+    }
+
+    let method = VerifyMethod::for_item(&item, &new_collection);
+    if method == VerifyMethod::VerifyCollectionV1 && !points_at(&item, &new_collection_mint) {
+        // Programmable items have no set-and-verify, so point them first
+        instructions.push(set_collection_v1_instruction(
+            &item.update_authority,
+            &payer_key,
+            &item,
+            &new_collection_mint,
+        ));
+    }
+    println!("Verifying into {} with {}", new_collection_mint, method);
+    instructions.push(verify_instruction(
+        method,
+        &authority_key,
+        new_record,
+        &payer_key,
+        &item_mint,
+        &item.update_authority,
+        &new_collection_mint,
+    ));
+
+    tx::submit(
+        cluster,
+        payer,
+        &[authority],
+        &instructions,
+        options,
+        "NFT moved to the new collection",
+    )
+    .map(Some)
+}
+
+/// `UpdateV1` that only points a programmable item at `collection_mint`,
+/// unverified.
+fn set_collection_v1_instruction(
+    authority: &Pubkey,
+    payer: &Pubkey,
+    item: &Metadata,
+    collection_mint: &Pubkey,
+) -> Instruction {
+    UpdateV1 {
+        authority: *authority,
+        delegate_record: None,
+        token: None,
+        mint: item.mint,
+        metadata: find_metadata_pda(&item.mint),
+        edition: Some(find_master_edition_pda(&item.mint)),
+        payer: *payer,
+        system_program: solana_sdk::system_program::ID,
+        sysvar_instructions: solana_sdk::sysvar::instructions::ID,
+        authorization_rules_program: None,
+        authorization_rules: None,
+    }
+    .instruction(UpdateV1InstructionArgs {
+        new_update_authority: None,
+        data: None,
+        primary_sale_happened: None,
+        is_mutable: None,
+        collection: CollectionToggle::Set(Collection {
+            verified: false,
+            key: *collection_mint,
+        }),
+        collection_details: CollectionDetailsToggle::None,
+        uses: UsesToggle::None,
+        rule_set: RuleSetToggle::None,
+        authorization_data: None,
+    })
+}
+
+pub fn find_collection_authority_record(collection_mint: &Pubkey, delegate: &Pubkey) -> Pubkey {
+    CollectionAuthorityRecord::find_pda(collection_mint, delegate).0
+}
+
+/// Checks that `authority` may verify and unverify items of `collection`: as
+/// its update authority (`None`) or through an approved collection authority
+/// record, which is returned so it can be passed to the instruction.
+pub fn collection_authority_record(
+    cluster: &ClusterConfig,
+    collection: &Metadata,
+    authority: &Pubkey,
+) -> Result<Option<Pubkey>, MetaLootError> {
+    if collection.update_authority == *authority {
+        return Ok(None);
+    }
+    let record = find_collection_authority_record(&collection.mint, authority);
+    match cluster
+        .client()
+        .get_account_with_commitment(&record, cluster.commitment)?
+        .value
+    {
+        Some(account) if account.owner == mpl_token_metadata::ID => Ok(Some(record)),
+        _ => Err(MetaLootError::InvalidInput(format!(
+            "{} is neither the update authority ({}) of collection {} nor an approved delegate",
+            authority, collection.update_authority, collection.mint
+        ))),
+    }
+}
+
+/// Approves `delegate` as a collection authority of `collection_mint`, so it
+/// can verify and unverify items. The payer must be the collection's update
+/// authority. Returns `None` when the delegate is already approved.
+pub fn approve_collection_authority(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    collection_mint: Pubkey,
+    delegate: Pubkey,
+    options: &TxOptions,
+) -> Result<Option<TxOutcome>, MetaLootError> {
+    let metadata = find_metadata_pda(&collection_mint);
+    let collection = inspect::fetch_decoded_metadata(cluster, &metadata)?;
+    if collection.update_authority != payer.pubkey() {
+        return Err(MetaLootError::InvalidInput(format!(
+            "only the update authority {} of collection {} can approve delegates",
+            collection.update_authority, collection_mint
+        )));
+    }
+    let record = find_collection_authority_record(&collection_mint, &delegate);
+    if cluster
+        .client()
+        .get_account_with_commitment(&record, cluster.commitment)?
+        .value
+        .is_some()
+    {
+        println!(
+            "{} is already a collection authority of {}",
+            delegate, collection_mint
+        );
+        return Ok(None);
+    }
+
+    let instruction = ApproveCollectionAuthority {
+        collection_authority_record: record,
+        new_collection_authority: delegate,
+        update_authority: payer.pubkey(),
+        payer: payer.pubkey(),
+        metadata,
+        mint: collection_mint,
+        system_program: solana_sdk::system_program::ID,
+        rent: None,
+    }
+    .instruction();
+    println!("Collection authority record: {}", record);
+    tx::submit(
+        cluster,
+        payer,
+        &[],
+        &[instruction],
+        options,
+        "Collection authority approved",
+    )
+    .map(Some)
+}
+
+/// Revokes `delegate` as a collection authority of `collection_mint`. The
+/// payer must be the collection's update authority or the delegate itself.
+/// Returns `None` when there was no record to revoke.
+pub fn revoke_collection_authority(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    collection_mint: Pubkey,
+    delegate: Pubkey,
+    options: &TxOptions,
+) -> Result<Option<TxOutcome>, MetaLootError> {
+    let metadata = find_metadata_pda(&collection_mint);
+    let collection = inspect::fetch_decoded_metadata(cluster, &metadata)?;
+    if payer.pubkey() != collection.update_authority && payer.pubkey() != delegate {
+        return Err(MetaLootError::InvalidInput(format!(
+            "only the update authority {} of collection {} or the delegate itself can revoke it",
+            collection.update_authority, collection_mint
+        )));
+    }
+    let record = find_collection_authority_record(&collection_mint, &delegate);
+    if cluster
+        .client()
+        .get_account_with_commitment(&record, cluster.commitment)?
+        .value
+        .is_none()
+    {
+        println!(
+            "{} is not a collection authority of {}",
+            delegate, collection_mint
+        );
+        return Ok(None);
+    }
+
+    let instruction = RevokeCollectionAuthority {
+        collection_authority_record: record,
+        delegate_authority: delegate,
+        revoke_authority: payer.pubkey(),
+        metadata,
+        mint: collection_mint,
+    }
+    .instruction();
+    tx::submit(
+        cluster,
+        payer,
+        &[],
+        &[instruction],
+        options,
+        "Collection authority revoked",
+    )
+    .map(Some)
+}
+
+/// An approved collection authority delegate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectionDelegate {
+    pub delegate: Pubkey,
+    pub record: Pubkey,
+}
+
+/// How many of the collection metadata's most recent transactions are
+/// scanned for approvals; each one costs an RPC call.
+pub const COLLECTION_HISTORY_LIMIT: usize = 1_000;
+
+/// First byte of `ApproveCollectionAuthority` instruction data, taken from
+/// the instruction mpl-token-metadata builds.
+fn approve_collection_authority_discriminator() -> u8 {
+    ApproveCollectionAuthority {
+        collection_authority_record: Pubkey::default(),
+        new_collection_authority: Pubkey::default(),
+        update_authority: Pubkey::default(),
+        payer: Pubkey::default(),
+        metadata: Pubkey::default(),
+        mint: Pubkey::default(),
+        system_program: Pubkey::default(),
+        rent: None,
+    }
+    .instruction()
+    .data[0]
+}
+
+/// Lists the delegates currently approved for `collection_mint`.
+///
+/// Records store neither the mint nor the delegate, so candidates are taken
+/// from the `ApproveCollectionAuthority` instructions in the last
+/// [`COLLECTION_HISTORY_LIMIT`] transactions of the collection metadata and
+/// kept if their record still exists. Approvals made through CPI or address
+/// lookup tables, and transactions the node has pruned, are not seen.
+pub fn list_collection_authorities(
+    cluster: &ClusterConfig,
+    collection_mint: &Pubkey,
+) -> Result<Vec<CollectionDelegate>, MetaLootError> {
+    let client = cluster.client();
+    let metadata = find_metadata_pda(collection_mint);
+    let approve = approve_collection_authority_discriminator();
+    let transaction_config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        max_supported_transaction_version: Some(0),
+        ..RpcTransactionConfig::default()
+    };
+
+    let mut candidates: Vec<Pubkey> = Vec::new();
+    let mut scanned = 0;
+    let mut before = None;
+    while scanned < COLLECTION_HISTORY_LIMIT {
+        let page = client.get_signatures_for_address_with_config(
+            &metadata,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                limit: Some(COLLECTION_HISTORY_LIMIT - scanned),
+                commitment: Some(cluster.commitment),
+                ..GetConfirmedSignaturesForAddress2Config::default()
+            },
+        )?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        scanned += page.len();
+        before = Some(parse_signature(&last.signature)?);
+        for status in page.iter().filter(|status| status.err.is_none()) {
+            let Ok(signature) = Signature::from_str(&status.signature) else {
+                continue;
+            };
+            // Pruned or unsupported transactions are skipped, not fatal
+            let Ok(transaction) =
+                client.get_transaction_with_config(&signature, transaction_config)
+            else {
+                continue;
+            };
+            let Some(transaction) = transaction.transaction.transaction.decode() else {
+                continue;
+            };
+            let keys = transaction.message.static_account_keys();
+            let key = |index: u8| keys.get(index as usize).copied();
+            for instruction in transaction.message.instructions() {
+                if key(instruction.program_id_index) != Some(mpl_token_metadata::ID)
+                    || instruction.data.first() != Some(&approve)
+                {
+                    continue;
+                }
+                // Accounts: record, new collection authority, ...
+                let delegate = instruction.accounts.get(1).and_then(|index| key(*index));
+                if let Some(delegate) = delegate.filter(|d| !candidates.contains(d)) {
+                    candidates.push(delegate);
+                }
+            }
+        }
+    }
+
+    let records: Vec<Pubkey> = candidates
+        .iter()
+        .map(|delegate| find_collection_authority_record(collection_mint, delegate))
+        .collect();
+    let mut delegates = Vec::new();
+    // getMultipleAccounts takes at most 100 keys
+    for (chunk, record_chunk) in candidates.chunks(100).zip(records.chunks(100)) {
+        let accounts = client.get_multiple_accounts(record_chunk)?;
+        for ((delegate, record), account) in chunk.iter().zip(record_chunk).zip(accounts) {
+            if account.is_some_and(|account| account.owner == mpl_token_metadata::ID) {
+                delegates.push(CollectionDelegate {
+                    delegate: *delegate,
+                    record: *record,
+                });
+            }
+        }
+    }
+    Ok(delegates)
+}
+
+fn parse_signature(signature: &str) -> Result<Signature, MetaLootError> {
+    Signature::from_str(signature).map_err(|err| MetaLootError::Decode {
+        account: Pubkey::default(),
+        message: format!("bad signature {}: {}", signature, err),
+    })
+}
+
+/// Whether `key` signs a transaction sent by `payer` with `authority`.
+fn signs(key: &Pubkey, payer: &dyn Signer, authority: &dyn Signer) -> bool {
+    *key == payer.pubkey() || *key == authority.pubkey()
+}
+
+fn is_programmable(item: &Metadata) -> bool {
+    matches!(
+        item.token_standard,
+        Some(TokenStandard::ProgrammableNonFungible)
+            | Some(TokenStandard::ProgrammableNonFungibleEdition)
+    )
+}
+
+fn points_at(item: &Metadata, collection_mint: &Pubkey) -> bool {
+    item.collection
+        .as_ref()
+        .is_some_and(|c| c.key == *collection_mint)
+}
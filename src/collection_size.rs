@@ -0,0 +1,240 @@
+use crate::{
+    cluster::ClusterConfig,
+    collection::collection_authority_record,
+    error::MetaLootError,
+    find_metadata_pda,
+    inspect::{self, decode_metadata},
+    tx, TxOptions, TxOutcome,
+};
+use mpl_token_metadata::{
+    accounts::Metadata,
+    instructions::{SetCollectionSize, SetCollectionSizeInstructionArgs},
+    types::{CollectionDetails, Key, SetCollectionSizeArgs},
+};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+use std::fmt;
+
+/// What a collection's `collection_details` record about its size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordedSize {
+    /// Legacy collection without details.
+    Unsized,
+    /// `CollectionDetails::V1`, which keeps a count of verified items.
+    Sized(u64),
+    /// `CollectionDetails::V2`: sized, but the metadata keeps no count.
+    SizedV2,
+}
+
+impl From<&Option<CollectionDetails>> for RecordedSize {
+    fn from(details: &Option<CollectionDetails>) -> Self {
+        match details {
+            Some(CollectionDetails::V1 { size }) => RecordedSize::Sized(*size),
+            Some(CollectionDetails::V2 { .. }) => RecordedSize::SizedV2,
+            None => RecordedSize::Unsized,
+        }
+    }
+}
+
+/// Verified items of a collection as found on-chain, next to the size the
+/// collection records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectionSizeReport {
+    pub collection_mint: Pubkey,
+    pub recorded_size: RecordedSize,
+    /// Mints of the items verified into the collection.
+    pub verified_items: Vec<Pubkey>,
+    /// Mints that name the collection without being verified into it.
+    pub unverified_items: Vec<Pubkey>,
+    /// The update authority whose metadata accounts were scanned.
+    pub scanned_authority: Pubkey,
+}
+
+impl CollectionSizeReport {
+    pub fn counted_size(&self) -> u64 {
+        self.verified_items.len() as u64
+    }
+
+    /// Whether the collection is sized and its size matches the count. V2
+    /// collections keep no count, so there is nothing to disagree with.
+    pub fn is_consistent(&self) -> bool {
+        match self.recorded_size {
+            RecordedSize::Sized(size) => size == self.counted_size(),
+            RecordedSize::SizedV2 => true,
+            RecordedSize::Unsized => false,
+        }
+    }
+}
+
+impl fmt::Display for CollectionSizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Collection:      {}", self.collection_mint)?;
+        writeln!(
+            f,
+            "Scanned items of update authority {}",
+            self.scanned_authority
+        )?;
+        match self.recorded_size {
+            RecordedSize::Sized(size) => writeln!(f, "Recorded size:   {}", size)?,
+            RecordedSize::SizedV2 => writeln!(f, "Recorded size:   none kept (V2 details)")?,
+            RecordedSize::Unsized => writeln!(f, "Recorded size:   none (unsized collection)")?,
+        }
+        writeln!(f, "Verified items:  {}", self.counted_size())?;
+        for mint in &self.verified_items {
+            writeln!(f, "  {}", mint)?;
+        }
+        if !self.unverified_items.is_empty() {
+            writeln!(f, "Named but not verified: {}", self.unverified_items.len())?;
+            for mint in &self.unverified_items {
+                writeln!(f, "  {}", mint)?;
+            }
+        }
+        match self.recorded_size {
+            RecordedSize::Sized(size) if size == self.counted_size() => {
+                writeln!(f, "Size is consistent.")
+            }
+            RecordedSize::Sized(size) => writeln!(
+                f,
+                "Size drift: recorded {} but {} verified ({:+})",
+                size,
+                self.counted_size(),
+                self.counted_size() as i128 - size as i128
+            ),
+            RecordedSize::SizedV2 => writeln!(f, "V2 details keep no size to reconcile."),
+            RecordedSize::Unsized => writeln!(
+                f,
+                "Unsized: can be migrated to a size of {}.",
+                self.counted_size()
+            ),
+        }
+    }
+}
+
+/// Scans the metadata accounts of `update_authority` (the collection's own
+/// update authority by default) for items naming `collection_mint`, and sets
+/// the verified ones against the size the collection records.
+///
+/// Metadata has no fixed offset for its collection field, so items are found
+/// through their update authority; items with another one are not counted.
+pub fn scan_collection_size(
+    cluster: &ClusterConfig,
+    collection_mint: Pubkey,
+    update_authority: Option<Pubkey>,
+) -> Result<CollectionSizeReport, MetaLootError> {
+    let collection =
+        inspect::fetch_decoded_metadata(cluster, &find_metadata_pda(&collection_mint))?;
+    scan_items(cluster, &collection, update_authority)
+}
+
+/// Scans for the items of an already decoded `collection`.
+fn scan_items(
+    cluster: &ClusterConfig,
+    collection: &Metadata,
+    update_authority: Option<Pubkey>,
+) -> Result<CollectionSizeReport, MetaLootError> {
+    let collection_mint = collection.mint;
+    let scanned_authority = update_authority.unwrap_or(collection.update_authority);
+
+    let accounts = cluster.client().get_program_accounts_with_config(
+        &mpl_token_metadata::ID,
+        RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, vec![Key::MetadataV1 as u8])),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    1,
+                    scanned_authority.to_bytes().to_vec(),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(cluster.commitment),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        },
+    )?;
+
+    let mut verified_items = Vec::new();
+    let mut unverified_items = Vec::new();
+    for (address, account) in accounts {
+        // Accounts that fail to decode cannot be collection items either
+        let Ok(item) = decode_metadata(&address, &account.data) else {
+            continue;
+        };
+        match item.collection {
+            Some(c) if c.key == collection_mint && c.verified => verified_items.push(item.mint),
+            Some(c) if c.key == collection_mint => unverified_items.push(item.mint),
+            _ => {}
+        }
+    }
+    verified_items.sort();
+    unverified_items.sort();
+
+    Ok(CollectionSizeReport {
+        collection_mint,
+        recorded_size: RecordedSize::from(&collection.collection_details),
+        verified_items,
+        unverified_items,
+        scanned_authority,
+    })
+}
+
+/// Scans the collection and, when it has no recorded size yet, sends
+/// `SetCollectionSize` with the counted size. `authority` is the collection's
+/// update authority or an approved delegate.
+///
+/// Token Metadata only accepts `SetCollectionSize` on unsized collections and
+/// has no other instruction that rewrites a recorded size, so drift on a
+/// sized collection is reported as invalid input instead of being sent, and
+/// V2 collections, which keep no size, are left alone.
+pub fn sync_collection_size(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    authority: &dyn Signer,
+    collection_mint: Pubkey,
+    update_authority: Option<Pubkey>,
+    options: &TxOptions,
+) -> Result<(CollectionSizeReport, Option<TxOutcome>), MetaLootError> {
+    let collection_metadata = find_metadata_pda(&collection_mint);
+    let collection = inspect::fetch_decoded_metadata(cluster, &collection_metadata)?;
+    let report = scan_items(cluster, &collection, update_authority)?;
+    print!("{}", report);
+    if report.is_consistent() {
+        return Ok((report, None));
+    }
+    if let RecordedSize::Sized(recorded) = report.recorded_size {
+        return Err(MetaLootError::InvalidInput(format!(
+            "collection {} is already sized ({} recorded, {} counted); \
+             SetCollectionSize only migrates unsized collections and cannot fix drift",
+            collection_mint,
+            recorded,
+            report.counted_size()
+        )));
+    }
+
+    let authority_record = collection_authority_record(cluster, &collection, &authority.pubkey())?;
+    let instruction = SetCollectionSize {
+        collection_metadata,
+        collection_authority: authority.pubkey(),
+        collection_mint,
+        collection_authority_record: authority_record,
+    }
+    .instruction(SetCollectionSizeInstructionArgs {
+        set_collection_size_args: SetCollectionSizeArgs {
+            size: report.counted_size(),
+        },
+    });
+    let outcome = tx::submit(
+        cluster,
+        payer,
+        &[authority],
+        &[instruction],
+        options,
+        "Collection size set",
+    )?;
+    Ok((report, Some(outcome)))
+}
@@ -0,0 +1,300 @@
+use crate::spec::MetadataSpec;
+use mpl_token_metadata::{
+    accounts::Metadata,
+    types::{Collection, Creator, DataV2},
+};
+use std::fmt::{self, Debug};
+
+/// One field whose on-chain value differs from the spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub current: String,
+    pub desired: String,
+}
+
+/// Field-level difference between an on-chain metadata account and a spec,
+/// together with the data to send so that only the changed fields move.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataDiff {
+    pub changes: Vec<FieldChange>,
+    /// The current on-chain data with the spec's changes applied.
+    pub data: DataV2,
+    /// `Some` only when mutability itself changes.
+    pub is_mutable: Option<bool>,
+}
+
+impl MetadataDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether anything besides `is_mutable` changes.
+    pub fn changes_data(&self) -> bool {
+        self.changes
+            .iter()
+            .any(|change| change.field != "is_mutable")
+    }
+}
+
+fn show_creators(creators: &[Creator]) -> String {
+    let creators = creators
+        .iter()
+        .map(|creator| {
+            let verified = if creator.verified { " verified" } else { "" };
+            format!("{}:{}%{}", creator.address, creator.share, verified)
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", creators.join(", "))
+}
+
+fn show_option<T: Debug>(value: &Option<T>) -> String {
+    match value {
+        Some(value) => format!("{:?}", value),
+        None => "none".to_string(),
+    }
+}
+
+/// Compares `current` with `spec` as its update authority would apply it.
+///
+/// Fields the spec leaves unset (no creators, no collection, no uses) keep
+/// their on-chain value, and creators that are already verified stay verified.
+pub fn diff_metadata(current: &Metadata, spec: &MetadataSpec) -> MetadataDiff {
+    let mut changes = Vec::new();
+    let mut change = |field, current: String, desired: String| {
+        if current != desired {
+            changes.push(FieldChange {
+                field,
+                current,
+                desired,
+            });
+        }
+    };
+
+    change("name", current.name.clone(), spec.name.clone());
+    change("symbol", current.symbol.clone(), spec.symbol.clone());
+    change("uri", current.uri.clone(), spec.uri.clone());
+    change(
+        "seller_fee_basis_points",
+        current.seller_fee_basis_points.to_string(),
+        spec.royalty.to_string(),
+    );
+
+    let current_creators = current.creators.clone().unwrap_or_default();
+    let creators = if spec.creators.is_empty() {
+        current_creators.clone()
+    } else {
+        spec.creators(&current.update_authority)
+            .into_iter()
+            .map(|mut creator| {
+                creator.verified |= current_creators
+                    .iter()
+                    .any(|existing| existing.address == creator.address && existing.verified);
+                creator
+            })
+            .collect()
+    };
+    change(
+        "creators",
+        show_creators(&current_creators),
+        show_creators(&creators),
+    );
+
+    let collection = match spec.collection {
+        Some(key) if current.collection.as_ref().map(|c| c.key) != Some(key) => Some(Collection {
+            verified: false,
+            key,
+        }),
+        _ => current.collection.clone(),
+    };
+    let collection_key = |collection: &Option<Collection>| collection.as_ref().map(|c| c.key);
+    change(
+        "collection",
+        show_option(&collection_key(&current.collection)),
+        show_option(&collection_key(&collection)),
+    );
+
+    let uses = spec.uses.clone().or_else(|| current.uses.clone());
+    change("uses", show_option(&current.uses), show_option(&uses));
+
+    let is_mutable = (current.is_mutable != spec.is_mutable).then_some(spec.is_mutable);
+    change(
+        "is_mutable",
+        current.is_mutable.to_string(),
+        spec.is_mutable.to_string(),
+    );
+
+    MetadataDiff {
+        changes,
+        data: DataV2 {
+            name: spec.name.clone(),
+            symbol: spec.symbol.clone(),
+            uri: spec.uri.clone(),
+            seller_fee_basis_points: spec.royalty,
+            creators: (!creators.is_empty()).then_some(creators),
+            collection,
+            uses,
+        },
+        is_mutable,
+    }
+}
+
+impl fmt::Display for MetadataDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes.");
+        }
+        for change in &self.changes {
+            writeln!(f, "{}:", change.field)?;
+            writeln!(f, "  - {}", change.current)?;
+            writeln!(f, "  + {}", change.desired)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec::CreatorSpec;
+    use mpl_token_metadata::types::{Key, UseMethod, Uses};
+    use solana_sdk::pubkey::Pubkey;
+
+    fn on_chain() -> Metadata {
+        let update_authority = Pubkey::new_unique();
+        Metadata {
+            key: Key::MetadataV1,
+            update_authority,
+            mint: Pubkey::new_unique(),
+            name: "MetaLoot".to_string(),
+            symbol: "MLT".to_string(),
+            uri: "https://example.com/metaloot.json".to_string(),
+            seller_fee_basis_points: 500,
+            creators: Some(vec![Creator {
+                address: update_authority,
+                verified: true,
+                share: 100,
+            }]),
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: None,
+            collection: None,
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        }
+    }
+
+    /// The spec that describes `current` exactly.
+    fn spec_of(current: &Metadata) -> MetadataSpec {
+        MetadataSpec {
+            name: current.name.clone(),
+            symbol: current.symbol.clone(),
+            uri: current.uri.clone(),
+            royalty: current.seller_fee_basis_points,
+            creators: current
+                .creators
+                .iter()
+                .flatten()
+                .map(|creator| CreatorSpec {
+                    address: creator.address,
+                    share: creator.share,
+                })
+                .collect(),
+            collection: current.collection.as_ref().map(|c| c.key),
+            uses: current.uses.clone(),
+            is_mutable: current.is_mutable,
+            collection_details: None,
+        }
+    }
+
+    fn fields(diff: &MetadataDiff) -> Vec<&'static str> {
+        diff.changes.iter().map(|change| change.field).collect()
+    }
+
+    #[test]
+    fn unchanged_spec_gives_an_empty_diff() {
+        let current = on_chain();
+        let diff = diff_metadata(&current, &spec_of(&current));
+        assert!(diff.is_empty());
+        assert_eq!(diff.is_mutable, None);
+        assert_eq!(diff.data.creators, current.creators);
+    }
+
+    #[test]
+    fn verified_creators_stay_verified() {
+        let mut current = on_chain();
+        let other = Pubkey::new_unique();
+        current.creators = Some(vec![
+            Creator {
+                address: current.update_authority,
+                verified: false,
+                share: 50,
+            },
+            Creator {
+                address: other,
+                verified: true,
+                share: 50,
+            },
+        ]);
+        let mut spec = spec_of(&current);
+        spec.creators[0].share = 40;
+        spec.creators[1].share = 60;
+
+        let diff = diff_metadata(&current, &spec);
+        assert_eq!(fields(&diff), vec!["creators"]);
+        let creators = diff.data.creators.unwrap();
+        assert_eq!(creators[1].address, other);
+        assert!(creators[1].verified);
+        assert_eq!(creators[1].share, 60);
+    }
+
+    #[test]
+    fn verification_only_change_is_emitted() {
+        let mut current = on_chain();
+        current.creators.as_mut().unwrap()[0].verified = false;
+
+        let diff = diff_metadata(&current, &spec_of(&current));
+        assert_eq!(fields(&diff), vec!["creators"]);
+        let change = &diff.changes[0];
+        assert!(!change.current.contains("verified"));
+        assert!(change.desired.ends_with(":100% verified]"));
+    }
+
+    #[test]
+    fn unset_spec_fields_keep_on_chain_values() {
+        let mut current = on_chain();
+        current.collection = Some(Collection {
+            verified: true,
+            key: Pubkey::new_unique(),
+        });
+        current.uses = Some(Uses {
+            use_method: UseMethod::Multiple,
+            remaining: 3,
+            total: 5,
+        });
+        let mut spec = spec_of(&current);
+        spec.creators.clear();
+        spec.collection = None;
+        spec.uses = None;
+
+        let diff = diff_metadata(&current, &spec);
+        assert!(diff.is_empty());
+        assert_eq!(diff.data.creators, current.creators);
+        assert_eq!(diff.data.collection, current.collection);
+        assert_eq!(diff.data.uses, current.uses);
+    }
+
+    #[test]
+    fn is_mutable_only_change_is_emitted() {
+        let current = on_chain();
+        let mut spec = spec_of(&current);
+        spec.is_mutable = false;
+
+        let diff = diff_metadata(&current, &spec);
+        assert_eq!(fields(&diff), vec!["is_mutable"]);
+        assert!(!diff.changes_data());
+        assert_eq!(diff.is_mutable, Some(false));
+    }
+}
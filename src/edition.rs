@@ -0,0 +1,298 @@
+use crate::{
+    cluster::ClusterConfig,
+    error::MetaLootError,
+    find_master_edition_pda, find_metadata_pda,
+    inspect::{self, EditionInfo},
+    token::{create_mint_instructions, mint_to},
+    tx, Action, TxOptions, TxOutcome,
+};
+use mpl_token_metadata::{
+    accounts::EditionMarker,
+    instructions::{
+        MintNewEditionFromMasterEditionViaToken,
+        MintNewEditionFromMasterEditionViaTokenInstructionArgs,
+    },
+    types::MintNewEditionFromMasterEditionViaTokenArgs,
+    EDITION_MARKER_BIT_SIZE,
+};
+use solana_sdk::{program_pack::Pack, pubkey::Pubkey, signer::Signer};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use std::fmt;
+
+/// Accounts created by `print_edition`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintedEdition {
+    pub mint: Pubkey,
+    /// The owner's associated token account, holding the single token.
+    pub token_account: Pubkey,
+    pub metadata_pda: Pubkey,
+    pub edition_pda: Pubkey,
+    /// Number of the print, starting at 1.
+    pub edition: u64,
+    pub outcome: TxOutcome,
+}
+
+/// Supply of a master edition and the print numbers already taken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditionUsage {
+    pub master_mint: Pubkey,
+    pub supply: u64,
+    /// `None` for unlimited prints.
+    pub max_supply: Option<u64>,
+    /// Edition numbers marked as printed, ascending.
+    pub taken: Vec<u64>,
+}
+
+impl EditionUsage {
+    pub fn is_taken(&self, edition: u64) -> bool {
+        self.taken.binary_search(&edition).is_ok()
+    }
+
+    pub fn is_sold_out(&self) -> bool {
+        self.max_supply.is_some_and(|max| self.supply >= max)
+    }
+
+    /// The lowest edition number not printed yet, if the max supply allows one.
+    pub fn next_free(&self) -> Option<u64> {
+        let next = (1..)
+            .zip(&self.taken)
+            .find(|(expected, taken)| expected != *taken)
+            .map_or(self.taken.len() as u64 + 1, |(expected, _)| expected);
+        match self.max_supply {
+            Some(max) if next > max => None,
+            _ => Some(next),
+        }
+    }
+}
+
+impl fmt::Display for EditionUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Master mint:  {}", self.master_mint)?;
+        match self.max_supply {
+            Some(max) => writeln!(f, "Supply:       {} / {}", self.supply, max)?,
+            None => writeln!(f, "Supply:       {} (unlimited)", self.supply)?,
+        }
+        if self.taken.is_empty() {
+            return writeln!(f, "No editions printed.");
+        }
+        let taken: Vec<String> = self.taken.iter().map(u64::to_string).collect();
+        writeln!(f, "Printed:      {}", taken.join(", "))?;
+        match self.next_free() {
+            Some(next) => writeln!(f, "Next free:    {}", next),
+            None => writeln!(f, "Sold out."),
+        }
+    }
+}
+
+/// PDA of the marker account recording which editions of `master_mint` in the
+/// range of `edition` have been printed. One marker covers 248 numbers.
+pub fn find_edition_marker_pda(master_mint: &Pubkey, edition: u64) -> Pubkey {
+    EditionMarker::find_pda(
+        master_mint,
+        &(edition / EDITION_MARKER_BIT_SIZE).to_string(),
+    )
+    .0
+}
+
+/// Byte and bit of `edition` in its marker's ledger, most significant bit first.
+fn marker_bit(edition: u64) -> (usize, u8) {
+    let offset = edition % EDITION_MARKER_BIT_SIZE;
+    ((offset / 8) as usize, 1 << (7 - offset % 8))
+}
+
+/// Reads the master edition of `master_mint` and the edition markers behind
+/// it. Markers are read until as many prints as the supply are found, the max
+/// supply is covered, or a whole batch of markers is missing.
+pub fn taken_editions(
+    cluster: &ClusterConfig,
+    master_mint: Pubkey,
+) -> Result<EditionUsage, MetaLootError> {
+    let client = cluster.client();
+    let master_edition_pda = find_master_edition_pda(&master_mint);
+    let account = client
+        .get_account_with_commitment(&master_edition_pda, client.commitment())?
+        .value
+        .ok_or(MetaLootError::AccountNotFound(master_edition_pda))?;
+    let (supply, max_supply) = match inspect::decode_edition(&master_edition_pda, &account.data)? {
+        EditionInfo::Master { supply, max_supply } => (supply, max_supply),
+        EditionInfo::Print { parent, .. } => {
+            return Err(MetaLootError::InvalidInput(format!(
+                "{} is a print of {}, not a master edition",
+                master_mint, parent
+            )))
+        }
+    };
+
+    let last_marker = max_supply.map(|max| max / EDITION_MARKER_BIT_SIZE);
+    let mut taken = Vec::new();
+    let mut marker = 0;
+    // getMultipleAccounts takes at most 100 keys
+    while (taken.len() as u64) < supply && last_marker.is_none_or(|last| marker <= last) {
+        let end = last_marker.map_or(marker + 100, |last| (marker + 100).min(last + 1));
+        let markers: Vec<Pubkey> = (marker..end)
+            .map(|number| find_edition_marker_pda(&master_mint, number * EDITION_MARKER_BIT_SIZE))
+            .collect();
+        let accounts = client.get_multiple_accounts(&markers)?;
+        if accounts.iter().all(Option::is_none) {
+            break;
+        }
+        for (number, (address, account)) in (marker..end).zip(markers.iter().zip(accounts)) {
+            let Some(account) = account else {
+                continue;
+            };
+            let ledger = EditionMarker::from_bytes(&account.data)
+                .map_err(|err| MetaLootError::Decode {
+                    account: *address,
+                    message: err.to_string(),
+                })?
+                .ledger;
+            let first = number * EDITION_MARKER_BIT_SIZE;
+            taken.extend(
+                (first..first + EDITION_MARKER_BIT_SIZE)
+                    .filter(|edition| {
+                        let (index, mask) = marker_bit(*edition);
+                        ledger[index] & mask != 0
+                    })
+                    .filter(|edition| max_supply.is_none_or(|max| *edition <= max)),
+            );
+        }
+        marker = end;
+    }
+
+    Ok(EditionUsage {
+        master_mint,
+        supply,
+        max_supply,
+        taken,
+    })
+}
+
+/// Prints edition `edition` (the lowest free number by default) of
+/// `master_mint` to `owner` in a single transaction: a 0-decimal mint, the
+/// owner's associated token account holding one token and the print's
+/// metadata and edition accounts.
+///
+/// The payer must hold the master token in its associated token account.
+/// Fails before sending when the number is taken or past the max supply.
+pub fn print_edition(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    master_mint: Pubkey,
+    mint: &dyn Signer,
+    owner: &Pubkey,
+    edition: Option<u64>,
+    options: &TxOptions,
+) -> Result<PrintedEdition, MetaLootError> {
+    let payer_key = payer.pubkey();
+    let mint_key = mint.pubkey();
+
+    let usage = taken_editions(cluster, master_mint)?;
+    if usage.is_sold_out() {
+        return Err(MetaLootError::InvalidInput(format!(
+            "master edition {} has printed all {} editions of its max supply",
+            master_mint, usage.supply
+        )));
+    }
+    let edition = match edition {
+        Some(0) => {
+            return Err(MetaLootError::InvalidInput(
+                "edition numbers start at 1".to_string(),
+            ))
+        }
+        Some(edition) => edition,
+        None => usage.next_free().ok_or_else(|| {
+            MetaLootError::InvalidInput(format!("no edition number of {} is free", master_mint))
+        })?,
+    };
+    if let Some(max) = usage.max_supply.filter(|max| edition > *max) {
+        return Err(MetaLootError::InvalidInput(format!(
+            "edition {} is past the max supply of {}",
+            edition, max
+        )));
+    }
+    if usage.is_taken(edition) {
+        return Err(MetaLootError::InvalidInput(format!(
+            "edition {} of {} is already printed",
+            edition, master_mint
+        )));
+    }
+
+    let master_metadata_pda = find_metadata_pda(&master_mint);
+    let master = inspect::fetch_decoded_metadata(cluster, &master_metadata_pda)?;
+    let master_token_account = get_associated_token_address(&payer_key, &master_mint);
+    let holds_master = cluster
+        .client()
+        .get_account_with_commitment(&master_token_account, cluster.commitment)?
+        .value
+        .and_then(|account| spl_token::state::Account::unpack(&account.data).ok())
+        .is_some_and(|account| account.amount == 1);
+    if !holds_master {
+        return Err(MetaLootError::InvalidInput(format!(
+            "payer {} does not hold the master token in {}",
+            payer_key, master_token_account
+        )));
+    }
+
+    let token_account = get_associated_token_address(owner, &mint_key);
+    let metadata_pda = find_metadata_pda(&mint_key);
+    let edition_pda = find_master_edition_pda(&mint_key);
+
+    // The print's mint and freeze authority move to its edition account
+    let mut instructions =
+        create_mint_instructions(cluster, &payer_key, &mint_key, 0, Some(&payer_key))?;
+    instructions.push(create_associated_token_account_idempotent(
+        &payer_key,
+        owner,
+        &mint_key,
+        &spl_token::ID,
+    ));
+    instructions.push(mint_to(&mint_key, &token_account, &payer_key, 1)?);
+    instructions.push(
+        MintNewEditionFromMasterEditionViaToken {
+            new_metadata: metadata_pda,
+            new_edition: edition_pda,
+            master_edition: find_master_edition_pda(&master_mint),
+            new_mint: mint_key,
+            edition_mark_pda: find_edition_marker_pda(&master_mint, edition),
+            new_mint_authority: payer_key,
+            payer: payer_key,
+            token_account_owner: payer_key,
+            token_account: master_token_account,
+            new_metadata_update_authority: master.update_authority,
+            metadata: master_metadata_pda,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: Some(solana_sdk::sysvar::rent::ID),
+        }
+        .instruction(MintNewEditionFromMasterEditionViaTokenInstructionArgs {
+            mint_new_edition_from_master_edition_via_token_args:
+                MintNewEditionFromMasterEditionViaTokenArgs { edition },
+        }),
+    );
+
+    println!("Mint: {}", mint_key);
+    println!("Token account: {}", token_account);
+    let description = format!("Edition {} of {} printed", edition, master_mint);
+    let outcome = tx::submit(
+        cluster,
+        payer,
+        &[mint],
+        &instructions,
+        options,
+        Action::new(&description).irreversible(format!(
+            "edition number {} of the master is used up for good",
+            edition
+        )),
+    )?;
+
+    Ok(PrintedEdition {
+        mint: mint_key,
+        token_account,
+        metadata_pda,
+        edition_pda,
+        edition,
+        outcome,
+    })
+}
@@ -0,0 +1,153 @@
+use crate::validate::ValidationErrors;
+use metaloot_escrow::error::MetaLootProgramError;
+use mpl_token_metadata::errors::MplTokenMetadataError;
+use num_traits::FromPrimitive;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_request::RpcError,
+};
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signer::SignerError,
+    transaction::TransactionError,
+};
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Everything the library can fail with, split by what a caller would do
+/// about it: retry, fix the input, or give up and surface the error.
+#[derive(Debug, Error)]
+pub enum MetaLootError {
+    /// The RPC node could not be reached or answered with an error.
+    #[error("RPC request failed: {0}")]
+    Rpc(#[source] Box<ClientError>),
+    /// The transaction was not confirmed before its blockhash expired.
+    #[error("transaction expired before it was confirmed, retry with a fresh blockhash")]
+    BlockhashExpired,
+    /// The Token Metadata program rejected an instruction.
+    #[error("token metadata program error {code:#x}: {error}")]
+    Metadata {
+        code: u32,
+        error: MplTokenMetadataError,
+    },
+    /// The escrow program rejected an instruction.
+    #[error("escrow program error {code:#x}: {error}")]
+    Escrow {
+        code: u32,
+        error: MetaLootProgramError,
+    },
+    /// Any other program returned a custom error code.
+    #[error("program {} failed with custom error {code:#x}", display_program(.program))]
+    Program { program: Option<Pubkey>, code: u32 },
+    /// The transaction failed for a reason that is not a custom program error.
+    #[error("transaction failed: {0}")]
+    Transaction(TransactionError),
+    #[error(transparent)]
+    Validation(#[from] ValidationErrors),
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    /// The user declined a confirmation prompt.
+    #[error("aborted: {0}")]
+    Aborted(String),
+    #[error("account {0} does not exist")]
+    AccountNotFound(Pubkey),
+    #[error("could not decode account {account}: {message}")]
+    Decode { account: Pubkey, message: String },
+    #[error("could not load keypair: {0}")]
+    Keypair(String),
+    #[error("could not sign transaction: {0}")]
+    Signing(#[from] SignerError),
+    #[error("could not load spec {}: {message}", .path.display())]
+    Spec { path: PathBuf, message: String },
+    #[error("off-chain request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+fn display_program(program: &Option<Pubkey>) -> String {
+    program.map_or_else(|| "unknown".to_string(), |program| program.to_string())
+}
+
+impl MetaLootError {
+    /// Errors worth retrying unchanged: network trouble and expired blockhashes.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            MetaLootError::BlockhashExpired => true,
+            MetaLootError::Http(err) => err.is_timeout() || err.is_connect(),
+            MetaLootError::Rpc(err) => matches!(
+                err.kind,
+                ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_)
+            ),
+            _ => false,
+        }
+    }
+
+    /// Maps a failed send, using `instructions` to tell which program raised
+    /// a custom error so metadata program codes can be decoded by name.
+    pub fn from_client_error(err: ClientError, instructions: &[Instruction]) -> Self {
+        if let ClientErrorKind::RpcError(RpcError::ForUser(message)) = &err.kind {
+            if message.contains("transaction expiration") {
+                return MetaLootError::BlockhashExpired;
+            }
+        }
+        match err.get_transaction_error() {
+            Some(err) => Self::from_transaction_error(err, instructions),
+            None => MetaLootError::Rpc(Box::new(err)),
+        }
+    }
+
+    pub fn from_transaction_error(err: TransactionError, instructions: &[Instruction]) -> Self {
+        match err {
+            TransactionError::BlockhashNotFound => MetaLootError::BlockhashExpired,
+            TransactionError::InstructionError(index, InstructionError::Custom(code)) => {
+                let program = instructions
+                    .get(index as usize)
+                    .map(|instruction| instruction.program_id);
+                Self::from_custom_error(program, code)
+            }
+            err => MetaLootError::Transaction(err),
+        }
+    }
+
+    pub fn from_custom_error(program: Option<Pubkey>, code: u32) -> Self {
+        match program {
+            Some(program) if program == mpl_token_metadata::ID => {
+                match MplTokenMetadataError::from_u32(code) {
+                    Some(error) => MetaLootError::Metadata { code, error },
+                    None => MetaLootError::Program {
+                        program: Some(program),
+                        code,
+                    },
+                }
+            }
+            program => MetaLootError::Program { program, code },
+        }
+    }
+
+    /// Names a custom error raised by the escrow program deployed at
+    /// `program_id`. The escrow has no fixed address, so callers that know
+    /// where it lives apply this to their errors; anything else passes through.
+    pub fn decode_escrow(self, program_id: &Pubkey) -> Self {
+        match self {
+            MetaLootError::Program {
+                program: Some(program),
+                code,
+            } if program == *program_id => match MetaLootProgramError::from_u32(code) {
+                Some(error) => MetaLootError::Escrow { code, error },
+                None => MetaLootError::Program {
+                    program: Some(program),
+                    code,
+                },
+            },
+            err => err,
+        }
+    }
+}
+
+impl From<ClientError> for MetaLootError {
+    fn from(err: ClientError) -> Self {
+        MetaLootError::from_client_error(err, &[])
+    }
+}
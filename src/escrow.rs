@@ -0,0 +1,302 @@
+use crate::{cluster::ClusterConfig, error::MetaLootError, tx, Action, TxOptions, TxOutcome};
+use borsh::BorshDeserialize;
+use metaloot_escrow::{
+    error::MetaLootProgramError,
+    instruction,
+    state::{ClaimReceipt, Config},
+};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signer::Signer};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+
+/// Reads the config account of the escrow program deployed at `program_id`.
+pub fn fetch_escrow_config(
+    cluster: &ClusterConfig,
+    program_id: &Pubkey,
+) -> Result<Config, MetaLootError> {
+    let client = cluster.client();
+    let config_pda = instruction::find_config_pda(program_id).0;
+    let account = client
+        .get_account_with_commitment(&config_pda, client.commitment())?
+        .value
+        .ok_or(MetaLootError::AccountNotFound(config_pda))?;
+    if account.owner != *program_id {
+        return Err(MetaLootError::Decode {
+            account: config_pda,
+            message: format!("owned by {}, not the escrow program", account.owner),
+        });
+    }
+    Config::deserialize(&mut &account.data[..]).map_err(|err| MetaLootError::Decode {
+        account: config_pda,
+        message: err.to_string(),
+    })
+}
+
+/// Reads the receipt of claim `claim_id` to `user`, `None` while the claim
+/// has not been paid.
+pub fn fetch_claim_receipt(
+    cluster: &ClusterConfig,
+    program_id: &Pubkey,
+    user: &Pubkey,
+    claim_id: u64,
+) -> Result<Option<ClaimReceipt>, MetaLootError> {
+    let client = cluster.client();
+    let receipt_pda = instruction::find_claim_receipt_pda(program_id, user, claim_id).0;
+    let account = client
+        .get_account_with_commitment(&receipt_pda, client.commitment())?
+        .value;
+    match account {
+        // Lamports sent to the address before the claim leave a system account
+        Some(account) if account.owner == *program_id => {
+            ClaimReceipt::deserialize(&mut &account.data[..])
+                .map(Some)
+                .map_err(|err| MetaLootError::Decode {
+                    account: receipt_pda,
+                    message: err.to_string(),
+                })
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Whether claim `claim_id` has been paid to `user`.
+pub fn is_claim_paid(
+    cluster: &ClusterConfig,
+    program_id: &Pubkey,
+    user: &Pubkey,
+    claim_id: u64,
+) -> Result<bool, MetaLootError> {
+    Ok(fetch_claim_receipt(cluster, program_id, user, claim_id)?.is_some())
+}
+
+/// Sends escrow instructions, naming any error the escrow program raises.
+fn submit(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    signers: &[&dyn Signer],
+    program_id: &Pubkey,
+    instructions: &[Instruction],
+    options: &TxOptions,
+    action: impl Into<Action>,
+) -> Result<TxOutcome, MetaLootError> {
+    tx::submit(cluster, payer, signers, instructions, options, action)
+        .map_err(|err| err.decode_escrow(program_id))
+}
+
+/// Creates the escrow token account for `mint` and the config recording
+/// `admin` as the only key allowed to pay out. `admin` must be the upgrade
+/// authority of the program.
+pub fn initialize_escrow(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    admin: &dyn Signer,
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    options: &TxOptions,
+) -> Result<TxOutcome, MetaLootError> {
+    let instructions = [instruction::initialize(program_id, &admin.pubkey(), mint)];
+    submit(
+        cluster,
+        payer,
+        &[admin],
+        program_id,
+        &instructions,
+        options,
+        "Escrow initialized",
+    )
+}
+
+/// Moves `amount` base units from the depositor's associated token account
+/// into escrow.
+pub fn deposit_to_escrow(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    depositor: &dyn Signer,
+    program_id: &Pubkey,
+    amount: u64,
+    options: &TxOptions,
+) -> Result<TxOutcome, MetaLootError> {
+    let config = fetch_escrow_config(cluster, program_id)?;
+    let source = get_associated_token_address(&depositor.pubkey(), &config.mint);
+    let instructions = [instruction::deposit(
+        program_id,
+        &depositor.pubkey(),
+        &source,
+        amount,
+    )];
+    submit(
+        cluster,
+        payer,
+        &[depositor],
+        program_id,
+        &instructions,
+        options,
+        "Deposited into escrow",
+    )
+}
+
+/// Builds a payout to the associated token account of `wallet`, creating it
+/// first if needed.
+fn payout_instructions(
+    payer: &Pubkey,
+    config: &Config,
+    wallet: &Pubkey,
+    payout: impl FnOnce(&Pubkey) -> Instruction,
+) -> Vec<Instruction> {
+    let destination = get_associated_token_address(wallet, &config.mint);
+    vec![
+        create_associated_token_account_idempotent(payer, wallet, &config.mint, &spl_token::ID),
+        payout(&destination),
+    ]
+}
+
+/// Pays `amount` base units from escrow back to `wallet`. Works while the
+/// escrow is paused.
+pub fn withdraw_from_escrow(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    admin: &dyn Signer,
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+    amount: u64,
+    options: &TxOptions,
+) -> Result<TxOutcome, MetaLootError> {
+    let config = fetch_escrow_config(cluster, program_id)?;
+    let instructions = payout_instructions(&payer.pubkey(), &config, wallet, |destination| {
+        instruction::withdraw(program_id, &admin.pubkey(), destination, amount)
+    });
+    submit(
+        cluster,
+        payer,
+        &[admin],
+        program_id,
+        &instructions,
+        options,
+        "Withdrawn from escrow",
+    )
+}
+
+/// Pays a reward of `amount` base units from escrow to `user` under
+/// `claim_id`. A claim id already paid to the user is refused before
+/// anything is sent; the program refuses it too, so a retried job cannot
+/// pay twice even when two runs race.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_from_escrow(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    admin: &dyn Signer,
+    program_id: &Pubkey,
+    user: &Pubkey,
+    amount: u64,
+    claim_id: u64,
+    options: &TxOptions,
+) -> Result<TxOutcome, MetaLootError> {
+    if is_claim_paid(cluster, program_id, user, claim_id)? {
+        let error = MetaLootProgramError::AlreadyClaimed;
+        return Err(MetaLootError::Escrow {
+            code: error as u32,
+            error,
+        });
+    }
+    let config = fetch_escrow_config(cluster, program_id)?;
+    let instructions = payout_instructions(&payer.pubkey(), &config, user, |destination| {
+        instruction::claim(
+            program_id,
+            &admin.pubkey(),
+            user,
+            destination,
+            amount,
+            claim_id,
+        )
+    });
+    submit(
+        cluster,
+        payer,
+        &[admin],
+        program_id,
+        &instructions,
+        options,
+        "Reward claimed from escrow",
+    )
+}
+
+/// Hands the admin role of the escrow to `new_admin`.
+pub fn set_escrow_admin(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    admin: &dyn Signer,
+    program_id: &Pubkey,
+    new_admin: &Pubkey,
+    options: &TxOptions,
+) -> Result<TxOutcome, MetaLootError> {
+    let instructions = [instruction::set_admin(
+        program_id,
+        &admin.pubkey(),
+        new_admin,
+    )];
+    let action = Action::new("Escrow admin changed").irreversible(format!(
+        "only {} can pay out or hand the role back",
+        new_admin
+    ));
+    submit(
+        cluster,
+        payer,
+        &[admin],
+        program_id,
+        &instructions,
+        options,
+        action,
+    )
+}
+
+/// Stops (`paused`) or resumes deposits and claims.
+pub fn pause_escrow(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    admin: &dyn Signer,
+    program_id: &Pubkey,
+    paused: bool,
+    options: &TxOptions,
+) -> Result<TxOutcome, MetaLootError> {
+    let instructions = [instruction::pause(program_id, &admin.pubkey(), paused)];
+    submit(
+        cluster,
+        payer,
+        &[admin],
+        program_id,
+        &instructions,
+        options,
+        if paused {
+            "Escrow paused"
+        } else {
+            "Escrow resumed"
+        },
+    )
+}
+
+/// Sends what is left in escrow to `wallet`, then closes the escrow and
+/// config accounts, returning their rent to the admin.
+pub fn close_escrow(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    admin: &dyn Signer,
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+    options: &TxOptions,
+) -> Result<TxOutcome, MetaLootError> {
+    let config = fetch_escrow_config(cluster, program_id)?;
+    let instructions = payout_instructions(&payer.pubkey(), &config, wallet, |destination| {
+        instruction::close_escrow(program_id, &admin.pubkey(), destination)
+    });
+    let action = Action::new("Escrow closed").irreversible("the escrow and its config are closed");
+    submit(
+        cluster,
+        payer,
+        &[admin],
+        program_id,
+        &instructions,
+        options,
+        action,
+    )
+}
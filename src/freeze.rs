@@ -0,0 +1,361 @@
+use crate::{
+    authority::append_json_line, cluster::ClusterConfig, collection_size, error::MetaLootError,
+    find_master_edition_pda, find_metadata_pda, inspect, safety, tx, Action, TxOptions, TxOutcome,
+};
+use mpl_token_metadata::{
+    accounts::Metadata,
+    instructions::{
+        UpdateMetadataAccountV2, UpdateMetadataAccountV2InstructionArgs, UpdateV1,
+        UpdateV1InstructionArgs,
+    },
+    types::{CollectionDetailsToggle, CollectionToggle, RuleSetToggle, TokenStandard, UsesToggle},
+};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use solana_sdk::{
+    hash::{hash, Hash},
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::Signer,
+};
+use std::{
+    fmt,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// What has to be typed to make metadata immutable.
+pub const FREEZE_CONFIRMATION: &str = "freeze";
+
+/// Where the CLI appends the hashes of frozen metadata unless told otherwise.
+pub const DEFAULT_FREEZE_RECORD: &str = "freeze-record.jsonl";
+
+/// One line of the freeze record, written for every mint that was frozen, so
+/// its off-chain JSON and image can later be checked against these hashes.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FreezeRecordEntry {
+    /// Unix time the freeze was confirmed, in seconds.
+    pub timestamp: u64,
+    pub rpc_url: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub mint: Pubkey,
+    #[serde_as(as = "DisplayFromStr")]
+    pub metadata_pda: Pubkey,
+    pub uri: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub json_hash: Hash,
+    pub image_uri: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub image_hash: Hash,
+    #[serde_as(as = "DisplayFromStr")]
+    pub signature: Signature,
+}
+
+/// The state a mint's metadata is frozen in, with hashes of the off-chain
+/// JSON and image as they were when checked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FreezeCheck {
+    pub metadata_pda: Pubkey,
+    pub metadata: Metadata,
+    /// SHA-256 of the bytes behind `metadata.uri`.
+    pub json_hash: Hash,
+    /// The `image` field of the off-chain JSON.
+    pub image_uri: String,
+    /// SHA-256 of the bytes behind `image_uri`.
+    pub image_hash: Hash,
+}
+
+impl fmt::Display for FreezeCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let metadata = &self.metadata;
+        writeln!(f, "Mint:             {}", metadata.mint)?;
+        writeln!(f, "Name:             {}", metadata.name)?;
+        writeln!(f, "Symbol:           {}", metadata.symbol)?;
+        writeln!(f, "URI:              {}", metadata.uri)?;
+        writeln!(f, "  sha256:         {}", self.json_hash)?;
+        writeln!(f, "Image:            {}", self.image_uri)?;
+        writeln!(f, "  sha256:         {}", self.image_hash)?;
+        writeln!(
+            f,
+            "Seller fee:       {:.2}%",
+            metadata.seller_fee_basis_points as f64 / 100.0
+        )?;
+        for creator in metadata.creators.iter().flatten() {
+            writeln!(
+                f,
+                "Creator:          {} ({}%{})",
+                creator.address,
+                creator.share,
+                if creator.verified { ", verified" } else { "" }
+            )?;
+        }
+        match &metadata.collection {
+            Some(collection) => writeln!(
+                f,
+                "Collection:       {}{}",
+                collection.key,
+                if collection.verified {
+                    " (verified)"
+                } else {
+                    " (not verified)"
+                }
+            )?,
+            None => writeln!(f, "Collection:       none")?,
+        }
+        writeln!(f, "Update authority: {}", metadata.update_authority)
+    }
+}
+
+/// Mints made immutable by `freeze_metadata`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FreezeOutcome {
+    /// The checked state of every mint that was frozen.
+    pub frozen: Vec<FreezeCheck>,
+    /// Mints whose metadata was immutable already.
+    pub already_frozen: Vec<Pubkey>,
+    /// One outcome per transaction.
+    pub outcomes: Vec<TxOutcome>,
+}
+
+/// Checks that the metadata of `mint` is ready to be frozen: its off-chain
+/// JSON and the image it names are both reachable, and get hashed so the
+/// frozen state can be compared against later. `None` when the metadata is
+/// immutable already.
+pub fn check_freeze(
+    cluster: &ClusterConfig,
+    mint: &Pubkey,
+) -> Result<Option<FreezeCheck>, MetaLootError> {
+    let metadata_pda = find_metadata_pda(mint);
+    let metadata = inspect::fetch_decoded_metadata(cluster, &metadata_pda)?;
+    if !metadata.is_mutable {
+        return Ok(None);
+    }
+
+    let json = inspect::fetch_off_chain_bytes(&metadata.uri, cluster.timeout)?;
+    let json_hash = hash(&json);
+    let value: serde_json::Value = serde_json::from_slice(&json).map_err(|err| {
+        MetaLootError::InvalidInput(format!("{} is not valid JSON: {}", metadata.uri, err))
+    })?;
+    let image_uri = match value.get("image").and_then(serde_json::Value::as_str) {
+        Some(image) if !image.is_empty() => image.to_string(),
+        _ => {
+            return Err(MetaLootError::InvalidInput(format!(
+                "{} has no image",
+                metadata.uri
+            )))
+        }
+    };
+    let image_hash = hash(&inspect::fetch_off_chain_bytes(
+        &image_uri,
+        cluster.timeout,
+    )?);
+
+    Ok(Some(FreezeCheck {
+        metadata_pda,
+        metadata,
+        json_hash,
+        image_uri,
+        image_hash,
+    }))
+}
+
+/// Builds the instruction setting `is_mutable` to false and nothing else.
+fn freeze_instruction(authority: &Pubkey, payer: &Pubkey, check: &FreezeCheck) -> Instruction {
+    let metadata = &check.metadata;
+    match metadata.token_standard {
+        // Programmable NFTs reject the legacy instruction
+        Some(TokenStandard::ProgrammableNonFungible)
+        | Some(TokenStandard::ProgrammableNonFungibleEdition) => UpdateV1 {
+            authority: *authority,
+            delegate_record: None,
+            token: None,
+            mint: metadata.mint,
+            metadata: check.metadata_pda,
+            edition: Some(find_master_edition_pda(&metadata.mint)),
+            payer: *payer,
+            system_program: solana_sdk::system_program::ID,
+            sysvar_instructions: solana_sdk::sysvar::instructions::ID,
+            authorization_rules_program: None,
+            authorization_rules: None,
+        }
+        .instruction(UpdateV1InstructionArgs {
+            new_update_authority: None,
+            data: None,
+            primary_sale_happened: None,
+            is_mutable: Some(false),
+            collection: CollectionToggle::None,
+            collection_details: CollectionDetailsToggle::None,
+            uses: UsesToggle::None,
+            rule_set: RuleSetToggle::None,
+            authorization_data: None,
+        }),
+        _ => UpdateMetadataAccountV2 {
+            metadata: check.metadata_pda,
+            update_authority: *authority,
+        }
+        .instruction(UpdateMetadataAccountV2InstructionArgs {
+            data: None,
+            new_update_authority: None,
+            primary_sale_happened: None,
+            is_mutable: Some(false),
+        }),
+    }
+}
+
+/// Makes the metadata of every mint in `mints` immutable for good.
+///
+/// All mints are checked first (see `check_freeze`) and nothing is sent if
+/// any check fails. The final state of each is then printed and
+/// `FREEZE_CONFIRMATION` must be typed, unless `assume_yes` is set or this is
+/// a dry run. `authority` must be the update authority of every mint. The
+/// hashes of every mint that was frozen are appended to `record` as JSON
+/// lines.
+#[allow(clippy::too_many_arguments)]
+pub fn freeze_metadata(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    authority: &dyn Signer,
+    mints: &[Pubkey],
+    assume_yes: bool,
+    record: Option<&Path>,
+    options: &TxOptions,
+) -> Result<FreezeOutcome, MetaLootError> {
+    let mut frozen = Vec::new();
+    let mut already_frozen = Vec::new();
+    let mut failures = Vec::new();
+    for mint in mints {
+        match check_freeze(cluster, mint) {
+            Ok(Some(check)) if check.metadata.update_authority != authority.pubkey() => failures
+                .push(format!(
+                    "{}: update authority is {}, not {}",
+                    mint,
+                    check.metadata.update_authority,
+                    authority.pubkey()
+                )),
+            Ok(Some(check)) => frozen.push(check),
+            Ok(None) => already_frozen.push(*mint),
+            Err(err) => failures.push(format!("{}: {}", mint, err)),
+        }
+    }
+    for mint in &already_frozen {
+        println!("Already immutable: {}", mint);
+    }
+    if !failures.is_empty() {
+        for failure in &failures {
+            println!("Not ready: {}", failure);
+        }
+        return Err(MetaLootError::InvalidInput(format!(
+            "{} of {} mints failed the freeze checks, nothing was frozen",
+            failures.len(),
+            mints.len()
+        )));
+    }
+    if frozen.is_empty() {
+        return Ok(FreezeOutcome {
+            frozen,
+            already_frozen,
+            outcomes: Vec::new(),
+        });
+    }
+
+    println!("Final state, frozen for good:");
+    for check in &frozen {
+        print!("\n{}", check);
+    }
+    println!();
+    if !options.dry_run && !assume_yes {
+        println!(
+            "{} metadata accounts will become immutable; this cannot be undone.",
+            frozen.len()
+        );
+        safety::confirm_typed(FREEZE_CONFIRMATION, "freeze")?;
+    }
+
+    let payer_key = payer.pubkey();
+    let groups = frozen
+        .iter()
+        .map(|check| vec![freeze_instruction(&authority.pubkey(), &payer_key, check)])
+        .collect();
+    let batches = tx::pack_instructions(&payer_key, groups);
+    let mut outcomes = Vec::new();
+    let mut checks = frozen.iter();
+    for (index, batch) in batches.iter().enumerate() {
+        let description = if batches.len() > 1 {
+            format!("Metadata frozen ({}/{})", index + 1, batches.len())
+        } else {
+            "Metadata frozen".to_string()
+        };
+        let action = Action::new(&description).irreversible(format!(
+            "metadata of {} mints can never be changed again",
+            batch.len()
+        ));
+        let outcome = tx::submit(cluster, payer, &[authority], batch, options, action)?;
+        // Batches keep the order of `frozen`, one instruction per mint
+        let batch_checks: Vec<&FreezeCheck> = checks.by_ref().take(batch.len()).collect();
+        if let (Some(signature), Some(path)) = (outcome.signature(), record) {
+            for check in batch_checks {
+                append_json_line(path, &record_entry(cluster, check, signature))?;
+            }
+            println!("Recorded in {}", path.display());
+        }
+        outcomes.push(outcome);
+    }
+
+    Ok(FreezeOutcome {
+        frozen,
+        already_frozen,
+        outcomes,
+    })
+}
+
+fn record_entry(
+    cluster: &ClusterConfig,
+    check: &FreezeCheck,
+    signature: &Signature,
+) -> FreezeRecordEntry {
+    FreezeRecordEntry {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs()),
+        rpc_url: cluster.rpc_url.clone(),
+        mint: check.metadata.mint,
+        metadata_pda: check.metadata_pda,
+        uri: check.metadata.uri.clone(),
+        json_hash: check.json_hash,
+        image_uri: check.image_uri.clone(),
+        image_hash: check.image_hash,
+        signature: *signature,
+    }
+}
+
+/// Freezes every item verified into `collection_mint`, as found by
+/// `scan_collection_size`. The collection parent itself is left mutable.
+#[allow(clippy::too_many_arguments)]
+pub fn freeze_collection(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    authority: &dyn Signer,
+    collection_mint: Pubkey,
+    update_authority: Option<Pubkey>,
+    assume_yes: bool,
+    record: Option<&Path>,
+    options: &TxOptions,
+) -> Result<FreezeOutcome, MetaLootError> {
+    let report = collection_size::scan_collection_size(cluster, collection_mint, update_authority)?;
+    println!(
+        "Collection {}: {} verified items",
+        collection_mint,
+        report.verified_items.len()
+    );
+    freeze_metadata(
+        cluster,
+        payer,
+        authority,
+        &report.verified_items,
+        assume_yes,
+        record,
+        options,
+    )
+}
@@ -0,0 +1,258 @@
+use crate::{
+    cluster::ClusterConfig, error::MetaLootError, find_master_edition_pda, find_metadata_pda,
+};
+use mpl_token_metadata::{
+    accounts::{Edition, MasterEdition, Metadata},
+    types::Key,
+};
+use serde::Serialize;
+use serde_with::{serde_as, DisplayFromStr};
+use solana_sdk::pubkey::Pubkey;
+use std::{fmt, time::Duration};
+
+/// Everything known about a mint's metadata, on-chain and off-chain.
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct MetadataReport {
+    #[serde_as(as = "DisplayFromStr")]
+    pub metadata_pda: Pubkey,
+    pub metadata: Metadata,
+    #[serde_as(as = "DisplayFromStr")]
+    pub edition_pda: Pubkey,
+    pub edition: Option<EditionInfo>,
+    /// Name of the parent collection, when the item points at one.
+    pub collection_name: Option<String>,
+    /// The JSON behind `metadata.uri`, if it could be fetched.
+    pub off_chain: Option<serde_json::Value>,
+    pub off_chain_error: Option<String>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EditionInfo {
+    Master {
+        supply: u64,
+        max_supply: Option<u64>,
+    },
+    Print {
+        #[serde_as(as = "DisplayFromStr")]
+        parent: Pubkey,
+        edition: u64,
+    },
+}
+
+/// Strings in the metadata account are zero-padded to their max length.
+fn trim_padding(value: &mut String) {
+    let trimmed = value.trim_end_matches('\0').len();
+    value.truncate(trimmed);
+}
+
+pub fn decode_metadata(account: &Pubkey, data: &[u8]) -> Result<Metadata, MetaLootError> {
+    let mut metadata = Metadata::from_bytes(data).map_err(|err| MetaLootError::Decode {
+        account: *account,
+        message: err.to_string(),
+    })?;
+    trim_padding(&mut metadata.name);
+    trim_padding(&mut metadata.symbol);
+    trim_padding(&mut metadata.uri);
+    Ok(metadata)
+}
+
+/// Fetches and decodes a metadata account, failing if it does not exist.
+pub fn fetch_decoded_metadata(
+    cluster: &ClusterConfig,
+    metadata_pda: &Pubkey,
+) -> Result<Metadata, MetaLootError> {
+    let client = cluster.client();
+    match client
+        .get_account_with_commitment(metadata_pda, client.commitment())?
+        .value
+    {
+        Some(account) => decode_metadata(metadata_pda, &account.data),
+        None => Err(MetaLootError::AccountNotFound(*metadata_pda)),
+    }
+}
+
+pub(crate) fn decode_edition(account: &Pubkey, data: &[u8]) -> Result<EditionInfo, MetaLootError> {
+    let decode_error = |err: std::io::Error| MetaLootError::Decode {
+        account: *account,
+        message: err.to_string(),
+    };
+    match data.first() {
+        Some(key) if *key == Key::MasterEditionV2 as u8 || *key == Key::MasterEditionV1 as u8 => {
+            let master = MasterEdition::from_bytes(data).map_err(decode_error)?;
+            Ok(EditionInfo::Master {
+                supply: master.supply,
+                max_supply: master.max_supply,
+            })
+        }
+        Some(key) if *key == Key::EditionV1 as u8 => {
+            let print = Edition::from_bytes(data).map_err(decode_error)?;
+            Ok(EditionInfo::Print {
+                parent: print.parent,
+                edition: print.edition,
+            })
+        }
+        _ => Err(MetaLootError::Decode {
+            account: *account,
+            message: "not an edition account".to_string(),
+        }),
+    }
+}
+
+/// Downloads the off-chain JSON a metadata `uri` points at, giving up after
+/// `timeout`.
+pub fn fetch_off_chain_json(
+    uri: &str,
+    timeout: Duration,
+) -> Result<serde_json::Value, MetaLootError> {
+    let bytes = fetch_off_chain_bytes(uri, timeout)?;
+    serde_json::from_slice(&bytes)
+        .map_err(|err| MetaLootError::InvalidInput(format!("{} is not valid JSON: {}", uri, err)))
+}
+
+/// Downloads whatever `uri` points at as raw bytes, e.g. to hash it, giving up
+/// after `timeout`.
+pub fn fetch_off_chain_bytes(uri: &str, timeout: Duration) -> Result<Vec<u8>, MetaLootError> {
+    let response = reqwest::blocking::Client::builder()
+        .timeout(timeout)
+        .build()?
+        .get(uri)
+        .send()?
+        .error_for_status()?;
+    Ok(response.bytes()?.to_vec())
+}
+
+/// Fetches and decodes the metadata account of `mint`, together with its
+/// edition account, its collection and the off-chain JSON.
+pub fn fetch_metadata(
+    cluster: &ClusterConfig,
+    mint: &Pubkey,
+) -> Result<MetadataReport, MetaLootError> {
+    let client = cluster.client();
+    let metadata_pda = find_metadata_pda(mint);
+    let edition_pda = find_master_edition_pda(mint);
+
+    let accounts = client.get_multiple_accounts(&[metadata_pda, edition_pda])?;
+    let metadata = match &accounts[0] {
+        Some(account) => decode_metadata(&metadata_pda, &account.data)?,
+        None => return Err(MetaLootError::AccountNotFound(metadata_pda)),
+    };
+    let edition = match &accounts[1] {
+        Some(account) => Some(decode_edition(&edition_pda, &account.data)?),
+        None => None,
+    };
+
+    let collection_name = match &metadata.collection {
+        Some(collection) => fetch_decoded_metadata(cluster, &find_metadata_pda(&collection.key))
+            .ok()
+            .map(|collection| collection.name),
+        None => None,
+    };
+
+    let (off_chain, off_chain_error) = match fetch_off_chain_json(&metadata.uri, cluster.timeout) {
+        Ok(json) => (Some(json), None),
+        Err(err) => (None, Some(err.to_string())),
+    };
+
+    Ok(MetadataReport {
+        metadata_pda,
+        metadata,
+        edition_pda,
+        edition,
+        collection_name,
+        off_chain,
+        off_chain_error,
+    })
+}
+
+impl fmt::Display for MetadataReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let metadata = &self.metadata;
+        let row = |f: &mut fmt::Formatter<'_>, field: &str, value: &dyn fmt::Display| {
+            writeln!(f, "{:<24} {}", field, value)
+        };
+
+        row(f, "Mint", &metadata.mint)?;
+        row(f, "Metadata PDA", &self.metadata_pda)?;
+        row(f, "Update authority", &metadata.update_authority)?;
+        row(f, "Name", &metadata.name)?;
+        row(f, "Symbol", &metadata.symbol)?;
+        row(f, "URI", &metadata.uri)?;
+        row(f, "Seller fee (bps)", &metadata.seller_fee_basis_points)?;
+        row(f, "Primary sale happened", &metadata.primary_sale_happened)?;
+        row(f, "Mutable", &metadata.is_mutable)?;
+        if let Some(standard) = &metadata.token_standard {
+            row(f, "Token standard", &format!("{:?}", standard))?;
+        }
+        for creator in metadata.creators.iter().flatten() {
+            let verified = if creator.verified {
+                "verified"
+            } else {
+                "unverified"
+            };
+            row(
+                f,
+                "Creator",
+                &format!("{} {}% ({})", creator.address, creator.share, verified),
+            )?;
+        }
+
+        match &metadata.collection {
+            Some(collection) => {
+                let verified = if collection.verified {
+                    "verified"
+                } else {
+                    "unverified"
+                };
+                let name = self.collection_name.as_deref().unwrap_or("unknown");
+                row(
+                    f,
+                    "Collection",
+                    &format!("{} \"{}\" ({})", collection.key, name, verified),
+                )?;
+            }
+            None => row(f, "Collection", &"none")?,
+        }
+        if let Some(details) = &metadata.collection_details {
+            row(f, "Collection details", &format!("{:?}", details))?;
+        }
+        if let Some(uses) = &metadata.uses {
+            row(
+                f,
+                "Uses",
+                &format!("{:?} {}/{}", uses.use_method, uses.remaining, uses.total),
+            )?;
+        }
+
+        match &self.edition {
+            Some(EditionInfo::Master { supply, max_supply }) => {
+                let max_supply = max_supply.map_or("unlimited".to_string(), |max| max.to_string());
+                row(
+                    f,
+                    "Master edition",
+                    &format!("{} (supply {} / {})", self.edition_pda, supply, max_supply),
+                )?;
+            }
+            Some(EditionInfo::Print { parent, edition }) => {
+                row(
+                    f,
+                    "Print edition",
+                    &format!("#{} of master {}", edition, parent),
+                )?;
+            }
+            None => row(f, "Edition", &"none")?,
+        }
+
+        match (&self.off_chain, &self.off_chain_error) {
+            (Some(json), _) => {
+                let pretty = serde_json::to_string_pretty(json).map_err(|_| fmt::Error)?;
+                writeln!(f, "Off-chain JSON:")?;
+                writeln!(f, "{}", pretty)
+            }
+            (None, Some(err)) => row(f, "Off-chain JSON", &format!("unavailable: {}", err)),
+            (None, None) => row(f, "Off-chain JSON", &"unavailable"),
+        }
+    }
+}
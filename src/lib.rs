@@ -1,308 +1,324 @@
 use mpl_token_metadata::{
+    accounts::Metadata,
     instructions::{
         CreateMasterEditionV3, CreateMasterEditionV3InstructionArgs, CreateMetadataAccountV3,
-        CreateMetadataAccountV3InstructionArgs, SetAndVerifyCollection, UpdateMetadataAccountV2,
-        UpdateMetadataAccountV2InstructionArgs, UpdateV1,
+        CreateMetadataAccountV3InstructionArgs, UpdateMetadataAccountV2,
+        UpdateMetadataAccountV2InstructionArgs, UpdateV1, UpdateV1InstructionArgs,
     },
     types::{
-        CollectionDetails, CollectionDetailsToggle, CollectionToggle, Creator, Data, DataV2,
-        RuleSetToggle, UsesToggle,
+        CollectionDetails, CollectionDetailsToggle, CollectionToggle, Data, RuleSetToggle,
+        TokenStandard, UsesToggle,
     },
-    ID as TOKEN_METADATA_PROGRAM_ID,
 };
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::{
-    pubkey::Pubkey,
-    signer::{keypair::Keypair, EncodableKey, Signer},
-    transaction::Transaction,
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signer::Signer};
+
+pub mod authority;
+pub mod cluster;
+pub mod collection;
+pub mod collection_size;
+pub mod diff;
+pub mod edition;
+pub mod error;
+pub mod escrow;
+pub mod freeze;
+pub mod inspect;
+pub mod safety;
+pub mod signer;
+pub mod spec;
+pub mod token;
+pub mod tx;
+pub mod validate;
+
+pub use authority::{
+    transfer_update_authority, validate_new_authority, AuthorityAuditEntry, AuthorityTransfer,
 };
-use std::{error::Error, str::FromStr};
-pub fn create_metadata(
-    client: &RpcClient,
-    payer: &Keypair,
+pub use cluster::ClusterConfig;
+pub use collection::{
+    approve_collection_authority, list_collection_authorities, move_collection_item,
+    revoke_collection_authority, unverify_collection_item, verify_collection_item,
+    CollectionDelegate, VerifyMethod,
+};
+pub use collection_size::{
+    scan_collection_size, sync_collection_size, CollectionSizeReport, RecordedSize,
+};
+pub use diff::{diff_metadata, FieldChange, MetadataDiff};
+pub use edition::{
+    find_edition_marker_pda, print_edition, taken_editions, EditionUsage, PrintedEdition,
+};
+pub use error::MetaLootError;
+pub use escrow::{
+    claim_from_escrow, close_escrow, deposit_to_escrow, fetch_claim_receipt, fetch_escrow_config,
+    initialize_escrow, is_claim_paid, pause_escrow, set_escrow_admin, withdraw_from_escrow,
+};
+pub use freeze::{
+    check_freeze, freeze_collection, freeze_metadata, FreezeCheck, FreezeOutcome, FreezeRecordEntry,
+};
+pub use inspect::{fetch_metadata, EditionInfo, MetadataReport};
+pub use safety::Action;
+pub use signer::{resolve_signer, SignerSource};
+pub use spec::{CreatorSpec, MetadataSpec};
+pub use token::{create_fungible_token, mint_nft, FungibleToken, MintedNft};
+pub use tx::{SimulationReport, TxOptions, TxOutcome};
+pub use validate::{validate_data, ValidationError, ValidationErrors};
+
+/// What `create_metadata` does when the metadata account already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnExisting {
+    /// Report the existing account and leave it alone.
+    #[default]
+    Skip,
+    /// Overwrite the existing account with the spec if they differ.
+    Update,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CreateOutcome {
+    Created(TxOutcome),
+    /// The account was already there; `up_to_date` tells whether it matches the spec.
+    AlreadyExists {
+        up_to_date: bool,
+    },
+    Updated(TxOutcome),
+}
+
+/// Builds the `CreateMetadataAccountV3` instruction for `spec`, with `payer`
+/// as mint authority, payer and update authority. The data is validated first.
+pub fn create_metadata_instruction(
+    payer: &Pubkey,
     mint_address: Pubkey,
     metadata_pda: Pubkey,
-) -> Result<(), Box<dyn Error>> {
+    spec: &MetadataSpec,
+) -> Result<Instruction, MetaLootError> {
+    let data = spec.data_v2(payer);
+    validate::validate_data(&data)?;
+
     // Define metadata arguments
     let metadata_args = CreateMetadataAccountV3InstructionArgs {
-        // name: "MetaLoot".to_string(),
-        // symbol: "MTL".to_string(),
-        // uri: "https://tzqzzuafkobkhygtccse.supabase.co/storage/v1/object/public/biz_touch/crypto-ql/metaloot.json".to_string(),
-        // seller_fee_basis_points: 500, // Set your seller fee basis points
-        // creators: Some(vec![Creator {
-        //     address: payer.pubkey(),
-        //     verified: true,
-        //     share: 100, // 100% share
-        // }]),
-        data: DataV2{
-            name: "MetaLoot Token".to_string(),
-            symbol:  "MTL".to_string(),
-            uri: "https://tzqzzuafkobkhygtccse.supabase.co/storage/v1/object/public/biz_touch/crypto-ql/metaloot.json".to_string(),
-            seller_fee_basis_points: 0,
-         creators: Some(vec![Creator {
-            address: payer.pubkey(),
-            verified: true,
-            share: 100, // 100% share
-        }]),
-            collection: None,
-            uses: None,
-        },
-        is_mutable: true,
-        collection_details: None,
+        data,
+        is_mutable: spec.is_mutable,
+        collection_details: spec.collection_details.clone(),
     };
 
-    // Create the instruction
-    let instruction = CreateMetadataAccountV3 {
+    Ok(CreateMetadataAccountV3 {
         metadata: metadata_pda,
         mint: mint_address,
-        mint_authority: payer.pubkey(),
-        payer: payer.pubkey(),
-        update_authority: (payer.pubkey(), true),
+        mint_authority: *payer,
+        payer: *payer,
+        update_authority: (*payer, true),
         system_program: solana_sdk::system_program::ID,
         rent: Some(solana_sdk::sysvar::rent::ID),
     }
-    .instruction(metadata_args);
-
-    // Get recent blockhash
-    let blockhash = client.get_latest_blockhash()?;
-    let transaction = Transaction::new_signed_with_payer(
-        &[instruction],
-        Some(&payer.pubkey()),
-        &[payer],
-        blockhash,
-    );
-
-    // Send and confirm transaction
-    let signature = client.send_and_confirm_transaction(&transaction)?;
-    println!("Metadata account created. Signature: {}", signature);
+    .instruction(metadata_args))
+}
 
-    Ok(())
+/// `description` plus the irreversible effects of creating `spec`'s metadata.
+pub(crate) fn metadata_action(description: &str, spec: &MetadataSpec) -> Action {
+    Action::new(description).irreversible_if(
+        !spec.is_mutable,
+        "metadata is created immutable (is_mutable=false) and can never be updated",
+    )
 }
 
-pub fn create_metadata_nfts_collection(
-    client: &RpcClient,
-    payer: &Keypair,
+/// Creates the metadata account of `mint_address`, or reports the existing
+/// one (and optionally updates it) instead of failing inside the program.
+pub fn create_metadata(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
     mint_address: Pubkey,
     metadata_pda: Pubkey,
-) -> Result<(), Box<dyn Error>> {
-    // Define metadata arguments
-    let metadata_args = CreateMetadataAccountV3InstructionArgs {
-        // name: "MetaLoot".to_string(),
-        // symbol: "MTL".to_string(),
-        // uri: "https://tzqzzuafkobkhygtccse.supabase.co/storage/v1/object/public/biz_touch/crypto-ql/metaloot.json".to_string(),
-        // seller_fee_basis_points: 500, // Set your seller fee basis points
-        // creators: Some(vec![Creator {
-        //     address: payer.pubkey(),
-        //     verified: true,
-        //     share: 100, // 100% share
-        // }]),
-        data: DataV2{
-            name: "MetaForge Rewards Collection".to_string(),
-            symbol:  "MFR".to_string(),
-            uri: "https://tzqzzuafkobkhygtccse.supabase.co/storage/v1/object/public/biz_touch/crypto-ql/Non-FungubleTokenCol.json".to_string(),
-            seller_fee_basis_points: 0,
-         creators: Some(vec![Creator {
-            address: payer.pubkey(),
-            verified: true,
-            share: 100, // 100% share
-        }]),
-            collection: None,
-            uses: None,
-        },
-        is_mutable: true,
-        collection_details: Some(CollectionDetails::V1 { size: 100 }),
-    };
-
-    // Create the instruction
-    let instruction = CreateMetadataAccountV3 {
-        metadata: metadata_pda,
-        mint: mint_address,
-        mint_authority: payer.pubkey(),
-        payer: payer.pubkey(),
-        update_authority: (payer.pubkey(), true),
-        system_program: solana_sdk::system_program::ID,
-        rent: Some(solana_sdk::sysvar::rent::ID),
+    spec: &MetadataSpec,
+    on_existing: OnExisting,
+    options: &TxOptions,
+) -> Result<CreateOutcome, MetaLootError> {
+    let instruction =
+        create_metadata_instruction(&payer.pubkey(), mint_address, metadata_pda, spec)?;
+
+    let master_edition_pda = find_master_edition_pda(&mint_address);
+    let accounts = cluster
+        .client()
+        .get_multiple_accounts(&[metadata_pda, master_edition_pda])?;
+    if accounts[1].is_some() {
+        println!("Master edition already exists: {}", master_edition_pda);
+    }
+    if let Some(account) = &accounts[0] {
+        let existing = inspect::decode_metadata(&metadata_pda, &account.data)?;
+        println!(
+            "Metadata already exists: {} (\"{}\", {})",
+            metadata_pda, existing.name, existing.symbol
+        );
+        let diff = diff::diff_metadata(&existing, spec);
+        if diff.is_empty() {
+            println!("On-chain metadata already matches the spec.");
+            return Ok(CreateOutcome::AlreadyExists { up_to_date: true });
+        }
+        return match on_existing {
+            OnExisting::Skip => {
+                print!("On-chain metadata differs from the spec:\n{}", diff);
+                println!("Leaving it unchanged.");
+                Ok(CreateOutcome::AlreadyExists { up_to_date: false })
+            }
+            OnExisting::Update => match update(cluster, payer, metadata_pda, spec, options)? {
+                Some(outcome) => Ok(CreateOutcome::Updated(outcome)),
+                None => Ok(CreateOutcome::AlreadyExists { up_to_date: true }),
+            },
+        };
     }
-    .instruction(metadata_args);
-
-    // Get recent blockhash
-    let blockhash = client.get_latest_blockhash()?;
-    let transaction = Transaction::new_signed_with_payer(
-        &[instruction],
-        Some(&payer.pubkey()),
-        &[payer],
-        blockhash,
-    );
 
     // Send and confirm transaction
-    let signature = client.send_and_confirm_transaction(&transaction)?;
-    println!("Metadata account created. Signature: {}", signature);
+    let outcome = tx::submit(
+        cluster,
+        payer,
+        &[],
+        &[instruction],
+        options,
+        metadata_action("Metadata account created", spec),
+    )?;
 
-    Ok(())
+    Ok(CreateOutcome::Created(outcome))
 }
 
-pub fn create_metadata_nfts(
-    client: &RpcClient,
-    payer: &Keypair,
+/// Same as `create_metadata`, but always marks the account as a sized collection
+/// parent. A spec without `collection_details` starts the count at zero.
+pub fn create_metadata_nfts_collection(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
     mint_address: Pubkey,
     metadata_pda: Pubkey,
-) -> Result<(), Box<dyn Error>> {
-    // // Define metadata arguments
-    // let metadata_args = CreateMetadataAccountV3InstructionArgs {
-    //     // name: "MetaLoot".to_string(),
-    //     // symbol: "MTL".to_string(),
-    //     // uri: "https://tzqzzuafkobkhygtccse.supabase.co/storage/v1/object/public/biz_touch/crypto-ql/metaloot.json".to_string(),
-    //     // seller_fee_basis_points: 500, // Set your seller fee basis points
-    //     // creators: Some(vec![Creator {
-    //     //     address: payer.pubkey(),
-    //     //     verified: true,
-    //     //     share: 100, // 100% share
-    //     // }]),
-    //     data: DataV2{
-    //         name: "MetaLian First Lander".to_string(),
-    //         symbol:  "MFL".to_string(),
-    //         uri: "https://tzqzzuafkobkhygtccse.supabase.co/storage/v1/object/public/biz_touch/crypto-ql/Non-FungubleToke.json".to_string(),
-    //         seller_fee_basis_points: 600,
-    //      creators: Some(vec![Creator {
-    //         address: payer.pubkey(),
-    //         verified: true,
-    //         share: 100, // 100% share
-    //     }]),
-    //         collection: Some(mpl_token_metadata::types::Collection {
-    //             verified: false,
-    //             // this is mint Address of the parent collection
-    //             key: Pubkey::from_str("53pYFioA1nhDoLrM8rGJFN4r8J1p1pmHstcchvjEng2h").unwrap(),
-    //         }),
-    //         uses: None,
-    //     },
-    //     is_mutable: false,
-    //     collection_details: None,
-    // };
-
-    // // Create the instruction
-    // let instruction = CreateMetadataAccountV3 {
-    //     metadata: metadata_pda,
-    //     mint: mint_address,
-    //     mint_authority: payer.pubkey(),
-    //     payer: payer.pubkey(),
-    //     update_authority: (payer.pubkey(), true),
-    //     system_program: solana_sdk::system_program::ID,
-    //     rent: Some(solana_sdk::sysvar::rent::ID),
-    // }
-    // .instruction(metadata_args);
-
-    // // Get recent blockhash
-    // let blockhash = client.get_latest_blockhash()?;
-    // let transaction = Transaction::new_signed_with_payer(
-    //     &[instruction],
-    //     Some(&payer.pubkey()),
-    //     &[payer],
-    //     blockhash,
-    // );
-
-    // // Send and confirm transaction
-    // let signature = client.send_and_confirm_transaction(&transaction)?;
-    // println!("Metadata account created. Signature: {}", signature);
-
-    // Step 2: Verify the Collection
-    let master_edition_pda = find_master_edition_pda(
-        &Pubkey::from_str("5iVF9QmA3vCgyyEk7UDJxNRGuFn54Da3mNdnGVrGYgjv").unwrap(),
-    );
-    println!("Master Edition PDA: {}", master_edition_pda.to_string());
-    let set_and_verify_collection = SetAndVerifyCollection {
-        metadata: metadata_pda,
-        collection_authority: payer.pubkey(),
-        payer: payer.pubkey(),
-        update_authority: payer.pubkey(),
-        collection_mint: Pubkey::from_str("5iVF9QmA3vCgyyEk7UDJxNRGuFn54Da3mNdnGVrGYgjv").unwrap(),
-        collection: Pubkey::from_str("HMVAGFDBAyBoPH8AwnZH7EtuY7sGBkXtfieuGANVzxBb").unwrap(),
-        // fix thies .......
-        collection_master_edition_account: master_edition_pda,
-        collection_authority_record: None, // Optional: Pass if you have a specific PDA
-    };
-
-    // Create the instruction using the struct
-    let verify_instruction = set_and_verify_collection.instruction();
-
-    // Create the transaction for verification
-    let verify_transaction = Transaction::new_signed_with_payer(
-        &[verify_instruction],
-        Some(&payer.pubkey()),
-        &[payer], // Only payer needs to sign since they are the collection authority
-        client.get_latest_blockhash()?,
-    );
-
-    let verify_signature = client.send_and_confirm_transaction(&verify_transaction)?;
-    println!(
-        "Collection verified for NFT. Signature: {}",
-        verify_signature
-    );
+    spec: &MetadataSpec,
+    on_existing: OnExisting,
+    options: &TxOptions,
+) -> Result<CreateOutcome, MetaLootError> {
+    let mut spec = spec.clone();
+    spec.collection_details
+        .get_or_insert(CollectionDetails::V1 { size: 0 });
+    create_metadata(
+        cluster,
+        payer,
+        mint_address,
+        metadata_pda,
+        &spec,
+        on_existing,
+        options,
+    )
+}
 
-    Ok(())
+/// Fetches the on-chain metadata and diffs it against `spec` without sending
+/// anything, so callers can preview an update.
+pub fn plan_update(
+    cluster: &ClusterConfig,
+    metadata_pda: Pubkey,
+    spec: &MetadataSpec,
+) -> Result<(Metadata, MetadataDiff), MetaLootError> {
+    let current = inspect::fetch_decoded_metadata(cluster, &metadata_pda)?;
+    let diff = diff::diff_metadata(&current, spec);
+    Ok((current, diff))
 }
 
+/// Prints the diff between the on-chain metadata and `spec` and sends an
+/// update only for what changed. Returns `None` when nothing had to change.
 pub fn update(
-    client: &RpcClient,
-    payer: &Keypair,
-    mint_address: Pubkey,
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
     metadata_pda: Pubkey,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Derive Metadata PDA
-    // let metadata_pda = Pubkey::find_program_address(
-    //     &[
-    //         b"metadata",
-    //         mpl_token_metadata::ID.as_ref(),
-    //         mint_address.as_ref(),
-    //     ],
-    //     &mpl_token_metadata::ID,
-    // )
-    // .0;
-
-    println!("Metadata PDA: {}", metadata_pda);
-
-    // Define Updated Metadata
-    let data = DataV2 {
-        name: "MetaLoot".to_string(),
-        symbol: "LOOT".to_string(),
-        uri: "https://tzqzzuafkobkhygtccse.supabase.co/storage/v1/object/public/biz_touch/crypto-ql/metaloot2.json".to_string(),
-        seller_fee_basis_points: 0,
-        creators: Some(vec![Creator {
-            address: payer.pubkey(),
-            verified: true,
-            share: 100,
-        }]),
-        collection: None,
-        uses: None,
-    };
+    spec: &MetadataSpec,
+    options: &TxOptions,
+) -> Result<Option<TxOutcome>, MetaLootError> {
+    let (current, diff) = plan_update(cluster, metadata_pda, spec)?;
+    print!("{}", diff);
+    if diff.is_empty() {
+        return Ok(None);
+    }
+    validate::validate_data(&diff.data)?;
+    if !current.is_mutable {
+        return Err(MetaLootError::InvalidInput(
+            "metadata is immutable and can no longer be updated".to_string(),
+        ));
+    }
+    if current.collection.as_ref().is_some_and(|c| c.verified)
+        && diff.data.collection != current.collection
+    {
+        return Err(MetaLootError::InvalidInput(
+            "the current collection is verified; unverify the item or move it to the new collection instead".to_string(),
+        ));
+    }
 
-    // Prepare the instruction arguments
-    let args = UpdateMetadataAccountV2InstructionArgs {
-        data: Some(data),
-        new_update_authority: None,  // None if you don't want to change it
-        primary_sale_happened: None, // None if no change
-        is_mutable: Some(true),      // None if no change
+    let data = diff.changes_data().then(|| diff.data.clone());
+    let update_instruction = match current.token_standard {
+        // Programmable NFTs reject the legacy instruction
+        Some(TokenStandard::ProgrammableNonFungible)
+        | Some(TokenStandard::ProgrammableNonFungibleEdition) => UpdateV1 {
+            authority: payer.pubkey(),
+            delegate_record: None,
+            token: None,
+            mint: current.mint,
+            metadata: metadata_pda,
+            edition: Some(find_master_edition_pda(&current.mint)),
+            payer: payer.pubkey(),
+            system_program: solana_sdk::system_program::ID,
+            sysvar_instructions: solana_sdk::sysvar::instructions::ID,
+            authorization_rules_program: None,
+            authorization_rules: None,
+        }
+        .instruction(UpdateV1InstructionArgs {
+            new_update_authority: None,
+            data: data.map(|data| Data {
+                name: data.name,
+                symbol: data.symbol,
+                uri: data.uri,
+                seller_fee_basis_points: data.seller_fee_basis_points,
+                creators: data.creators,
+            }),
+            primary_sale_happened: None,
+            is_mutable: diff.is_mutable,
+            collection: match &diff.data.collection {
+                collection if *collection == current.collection => CollectionToggle::None,
+                Some(collection) => CollectionToggle::Set(collection.clone()),
+                None => CollectionToggle::Clear,
+            },
+            collection_details: CollectionDetailsToggle::None,
+            uses: match &diff.data.uses {
+                uses if *uses == current.uses => UsesToggle::None,
+                Some(uses) => UsesToggle::Set(uses.clone()),
+                None => UsesToggle::Clear,
+            },
+            rule_set: RuleSetToggle::None,
+            authorization_data: None,
+        }),
+        _ => UpdateMetadataAccountV2 {
+            metadata: metadata_pda,
+            update_authority: payer.pubkey(),
+        }
+        .instruction(UpdateMetadataAccountV2InstructionArgs {
+            data,
+            new_update_authority: None, // None if you don't want to change it
+            primary_sale_happened: None, // None if no change
+            is_mutable: diff.is_mutable, // None if no change
+        }),
     };
 
-    // Create the instruction using the builder pattern
-    let update_instruction = UpdateMetadataAccountV2 {
-        metadata: metadata_pda,
-        update_authority: payer.pubkey(),
-    }
-    .instruction(args);
-
     // Create and send the transaction
-    let recent_blockhash = client.get_latest_blockhash()?;
-    let transaction = Transaction::new_signed_with_payer(
+    let outcome = tx::submit(
+        cluster,
+        payer,
+        &[],
         &[update_instruction],
-        Some(&payer.pubkey()),
-        &[payer],
-        recent_blockhash,
-    );
-
-    let signature = client.send_and_confirm_transaction(&transaction)?;
-    println!("Metadata updated successfully. Signature: {}", signature);
+        options,
+        Action::new("Metadata updated successfully").irreversible_if(
+            diff.is_mutable == Some(false),
+            "metadata becomes immutable (is_mutable=false) and can never be updated again",
+        ),
+    )?;
+
+    Ok(Some(outcome))
+}
 
-    Ok(())
+pub fn find_metadata_pda(mint: &Pubkey) -> Pubkey {
+    let seeds = &[
+        b"metadata",                     // "metadata"
+        mpl_token_metadata::ID.as_ref(), // Metaplex Program ID
+        mint.as_ref(),                   // Mint Address
+    ];
+    Pubkey::find_program_address(seeds, &mpl_token_metadata::ID).0
 }
 
 pub fn find_master_edition_pda(mint: &Pubkey) -> Pubkey {
@@ -315,12 +331,17 @@ pub fn find_master_edition_pda(mint: &Pubkey) -> Pubkey {
     Pubkey::find_program_address(seeds, &mpl_token_metadata::ID).0
 }
 
+/// Creates the master edition of `mint_address`, allowing at most
+/// `max_supply` printed editions (`None` for unlimited). The program enforces
+/// the cap when printing and it cannot be raised later.
 pub fn update_nfts_collection(
-    client: &RpcClient,
-    payer: &Keypair,
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
     mint_address: Pubkey,
     metadata_pda: Pubkey,
-) -> Result<(), Box<dyn std::error::Error>> {
+    max_supply: Option<u64>,
+    options: &TxOptions,
+) -> Result<TxOutcome, MetaLootError> {
     // // Derive Metadata PDA
     // // let metadata_pda = Pubkey::find_program_address(
     // //     &[
@@ -377,50 +398,57 @@ pub fn update_nfts_collection(
     // println!("Metadata updated successfully. Signature: {}", signature);
 
     // Step 2: Create Master Edition
-    let create_master_edition_instruction = CreateMasterEditionV3 {
+    // `None` allows unlimited prints
+    let create_master_edition_instruction =
+        create_master_edition_instruction(&payer.pubkey(), mint_address, metadata_pda, max_supply);
+
+    // Send Master Edition Creation Transaction
+    tx::submit(
+        cluster,
+        payer,
+        &[],
+        &[create_master_edition_instruction],
+        options,
+        master_edition_action("Master Edition account created"),
+    )
+}
+
+/// Builds the `CreateMasterEditionV3` instruction, with `payer` as update and
+/// mint authority. `max_supply` of `None` allows unlimited prints.
+pub fn create_master_edition_instruction(
+    payer: &Pubkey,
+    mint_address: Pubkey,
+    metadata_pda: Pubkey,
+    max_supply: Option<u64>,
+) -> Instruction {
+    CreateMasterEditionV3 {
         edition: find_master_edition_pda(&mint_address),
         mint: mint_address,
-        update_authority: payer.pubkey(),
-        mint_authority: payer.pubkey(),
-        payer: payer.pubkey(),
+        update_authority: *payer,
+        mint_authority: *payer,
+        payer: *payer,
         metadata: metadata_pda,
         token_program: spl_token::ID,
         system_program: solana_sdk::system_program::ID,
         rent: Some(solana_sdk::sysvar::rent::ID),
     }
-    .instruction(CreateMasterEditionV3InstructionArgs { max_supply: None }); // Unlimited collection size
-
-    // Send Master Edition Creation Transaction
-    let master_edition_tx = Transaction::new_signed_with_payer(
-        &[create_master_edition_instruction],
-        Some(&payer.pubkey()),
-        &[payer],
-        client.get_latest_blockhash()?,
-    );
-    let master_edition_signature = client.send_and_confirm_transaction(&master_edition_tx)?;
-    println!(
-        "Master Edition account created. Signature: {}",
-        master_edition_signature
-    );
-
-    Ok(())
+    .instruction(CreateMasterEditionV3InstructionArgs { max_supply })
 }
 
-pub fn get_json_key(
-    client: &RpcClient,
-    payer: &Keypair,
-    mint_address: Pubkey,
-    metadata_pda: Pubkey,
-) -> Result<(), Box<dyn Error>> {
-    // Read keypair from file
-    let keypair = Keypair::read_from_file("/Users/copycoder/metaloot-keypair.json")?;
-    
-    // Get public and private keys
-    let public_key = keypair.pubkey();
-    let private_key = keypair.secret();
-
-    println!("Public key: {}", public_key);
-    println!("Private key2: {:?}", private_key);
+/// `description` plus the irreversible effects of creating a master edition.
+pub(crate) fn master_edition_action(description: &str) -> Action {
+    Action::new(description).irreversible(
+        "mint and freeze authority move to the master edition, no more tokens can be minted",
+    )
+}
 
+/// Prints the public key of the keypair `source` resolves to, and its secret
+/// key bytes only when `show_secret` is set.
+pub fn get_json_key(source: &SignerSource, show_secret: bool) -> Result<(), MetaLootError> {
+    let keypair = signer::resolve_keypair(source)?;
+    println!("Public key: {}", keypair.pubkey());
+    if show_secret {
+        println!("Secret key: {:?}", keypair.secret_bytes());
+    }
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,74 +1,458 @@
-use mpl_token_metadata::{
-    instructions::{UpdateV1, UpdateV1InstructionArgs},
-    types::{CollectionDetailsToggle, CollectionToggle, Creator, RuleSetToggle, UsesToggle},
-    ID as TOKEN_METADATA_PROGRAM_ID,
+use clap::Parser;
+use cli::{
+    Cli, CollectionCommand, Command, DelegateCommand, EscrowCommand, KeypairCommand,
+    MasterEditionCommand, MetadataCommand, NftCommand, TokenCommand,
 };
-use solana_client::rpc_client::RpcClient;
-use solana_program::system_program;
-use solana_sdk::{
-    pubkey::Pubkey,
-    signer::{keypair::Keypair, Signer},
-    transaction::Transaction,
-};
-use std::{io, str::FromStr};
-pub mod lib;
+use my_project as lib;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Choose network:");
-    println!("1. Devnet");
-    println!("2. Testnet"); 
-    println!("3. Mainnet");
+mod cli;
 
-    let mut network_choice = String::new();
-    io::stdin().read_line(&mut network_choice)?;
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Cli::parse();
 
-    let rpc_url = match network_choice.trim() {
-        "1" => "https://api.devnet.solana.com",
-        "2" => "https://api.testnet.solana.com",
-        "3" => "https://api.mainnet-beta.solana.com",
-        _ => {
-            println!("Invalid choice, defaulting to devnet");
-            "https://api.devnet.solana.com"
-        }
+    let signer_source = cli.signer_source()?;
+    let options = cli.tx_options();
+    let command = match cli.command.take() {
+        Some(command) => command,
+        None => cli::prompt_command()?,
     };
-    let client = RpcClient::new(rpc_url);
 
-    // Load the payer's keypair
-    let payer =
-        <solana_sdk::signature::Keypair as solana_sdk::signer::EncodableKey>::read_from_file(
-            "/Users/copycoder/metaloot-keypair.json",
-        )?;
+    // Only commands that talk to the chain ask for a network
+    if let Command::Keypair(KeypairCommand::Show(args)) = &command {
+        lib::get_json_key(&signer_source, args.show_secret)?;
+        return Ok(());
+    }
+    let cluster = cli.cluster()?;
 
-    println!("Please enter the mint address:");
-    let mut mint_address_input = String::new();
-    io::stdin().read_line(&mut mint_address_input)?;
-    let mint_address = Pubkey::from_str(mint_address_input.trim())?;
+    // The payer is only loaded by commands that sign, so read-only commands
+    // work without a keypair
+    let load_payer = || lib::resolve_signer(&signer_source);
 
-    // Derive Metadata PDA
-    let metadata_pda = Pubkey::find_program_address(
-        &[
-            b"metadata",
-            TOKEN_METADATA_PROGRAM_ID.as_ref(),
-            mint_address.as_ref(),
-        ],
-        &TOKEN_METADATA_PROGRAM_ID,
-    )
-    .0;
+    // New mints get a fresh keypair unless one is given, e.g. for a vanity address
+    let load_mint =
+        |source: &Option<lib::SignerSource>| -> Result<Box<dyn Signer>, lib::MetaLootError> {
+            match source {
+                Some(source) => lib::resolve_signer(source),
+                None => Ok(Box::new(Keypair::new())),
+            }
+        };
 
-    println!("Metadata PDA: {}", metadata_pda);
+    // Collection commands sign for the collection with the payer unless told otherwise
+    let load_authority =
+        |args: &cli::AuthorityArgs| -> Result<Option<Box<dyn Signer>>, lib::MetaLootError> {
+            args.authority.as_ref().map(lib::resolve_signer).transpose()
+        };
 
-    println!("Choose operation:");
-    println!("1. Create Metadata");
-    println!("2. Update Metadata");
-    
-    let mut choice = String::new();
-    io::stdin().read_line(&mut choice)?;
+    let metadata_pda = |mint: &Pubkey| {
+        let metadata_pda = lib::find_metadata_pda(mint);
+        println!("Metadata PDA: {}", metadata_pda);
+        metadata_pda
+    };
 
-    match choice.trim() {
-        "1" => lib::get_json_key(&client, &payer, mint_address, metadata_pda)?,
-        "2" => lib::update_nfts_collection(&client, &payer, mint_address, metadata_pda)?,
-        _ => println!("Invalid choice"),
+    match command {
+        Command::Metadata(MetadataCommand::Create(args)) => {
+            let mint = args.mint_spec.mint.mint()?;
+            let spec = lib::MetadataSpec::from_file(args.mint_spec.spec()?)?;
+            let payer = load_payer()?;
+            lib::create_metadata(
+                &cluster,
+                payer.as_ref(),
+                mint,
+                metadata_pda(&mint),
+                &spec,
+                args.on_existing(),
+                &options,
+            )?;
+        }
+        Command::Metadata(MetadataCommand::Update(args)) => {
+            let mint = args.mint_spec.mint.mint()?;
+            let spec = lib::MetadataSpec::from_file(args.mint_spec.spec()?)?;
+            if args.preview {
+                let (_, diff) = lib::plan_update(&cluster, metadata_pda(&mint), &spec)?;
+                print!("{}", diff);
+                lib::validate_data(&diff.data)?;
+            } else {
+                let payer = load_payer()?;
+                lib::update(
+                    &cluster,
+                    payer.as_ref(),
+                    metadata_pda(&mint),
+                    &spec,
+                    &options,
+                )?;
+            }
+        }
+        Command::Metadata(MetadataCommand::Show(args)) => {
+            let report = lib::fetch_metadata(&cluster, &args.mint.mint()?)?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print!("{}", report);
+            }
+        }
+        Command::Metadata(MetadataCommand::TransferAuthority(args)) => {
+            let mint = args.mint.mint()?;
+            let payer = load_payer()?;
+            let authority = args
+                .authority
+                .as_ref()
+                .map(lib::resolve_signer)
+                .transpose()?;
+            let cosigner = args.cosign.as_ref().map(lib::resolve_signer).transpose()?;
+            lib::transfer_update_authority(
+                &cluster,
+                payer.as_ref(),
+                authority.as_deref().unwrap_or(payer.as_ref()),
+                mint,
+                args.new_authority,
+                cosigner.as_deref(),
+                Some(&args.audit_log),
+                &options,
+            )?;
+        }
+        Command::Metadata(MetadataCommand::Freeze(args)) => {
+            let mint = args.mint.mint()?;
+            let payer = load_payer()?;
+            let authority = args
+                .authority
+                .as_ref()
+                .map(lib::resolve_signer)
+                .transpose()?;
+            lib::freeze_metadata(
+                &cluster,
+                payer.as_ref(),
+                authority.as_deref().unwrap_or(payer.as_ref()),
+                &[mint],
+                args.yes,
+                Some(&args.record),
+                &options,
+            )?;
+        }
+        Command::Collection(CollectionCommand::Create(args)) => {
+            let mint = args.mint_spec.mint.mint()?;
+            let spec = lib::MetadataSpec::from_file(args.mint_spec.spec()?)?;
+            let payer = load_payer()?;
+            lib::create_metadata_nfts_collection(
+                &cluster,
+                payer.as_ref(),
+                mint,
+                metadata_pda(&mint),
+                &spec,
+                args.on_existing(),
+                &options,
+            )?;
+        }
+        Command::Collection(CollectionCommand::Verify(args)) => {
+            let mint = args.mint.mint()?;
+            let collection = match args.collection {
+                Some(collection) => collection,
+                None => match lib::inspect::fetch_decoded_metadata(&cluster, &metadata_pda(&mint))?
+                    .collection
+                {
+                    Some(collection) => collection.key,
+                    None => cli::prompt_collection()?,
+                },
+            };
+            let payer = load_payer()?;
+            let authority = load_authority(&args.authority)?;
+            lib::verify_collection_item(
+                &cluster,
+                payer.as_ref(),
+                authority.as_deref().unwrap_or(payer.as_ref()),
+                mint,
+                collection,
+                &options,
+            )?;
+        }
+        Command::Collection(CollectionCommand::Unverify(args)) => {
+            let mint = args.mint.mint()?;
+            let payer = load_payer()?;
+            let authority = load_authority(&args.authority)?;
+            lib::unverify_collection_item(
+                &cluster,
+                payer.as_ref(),
+                authority.as_deref().unwrap_or(payer.as_ref()),
+                mint,
+                &options,
+            )?;
+        }
+        Command::Collection(CollectionCommand::Move(args)) => {
+            let mint = args.mint.mint()?;
+            let collection = args.collection.collection()?;
+            let payer = load_payer()?;
+            let authority = load_authority(&args.authority)?;
+            lib::move_collection_item(
+                &cluster,
+                payer.as_ref(),
+                authority.as_deref().unwrap_or(payer.as_ref()),
+                mint,
+                collection,
+                &options,
+            )?;
+        }
+        Command::Collection(CollectionCommand::Size(args)) => {
+            let collection = args.collection.collection()?;
+            if args.fix {
+                let payer = load_payer()?;
+                let authority = load_authority(&args.authority)?;
+                lib::sync_collection_size(
+                    &cluster,
+                    payer.as_ref(),
+                    authority.as_deref().unwrap_or(payer.as_ref()),
+                    collection,
+                    args.update_authority,
+                    &options,
+                )?;
+            } else {
+                let report =
+                    lib::scan_collection_size(&cluster, collection, args.update_authority)?;
+                print!("{}", report);
+            }
+        }
+        Command::Collection(CollectionCommand::Freeze(args)) => {
+            let collection = args.collection.collection()?;
+            let payer = load_payer()?;
+            let authority = args
+                .authority
+                .as_ref()
+                .map(lib::resolve_signer)
+                .transpose()?;
+            lib::freeze_collection(
+                &cluster,
+                payer.as_ref(),
+                authority.as_deref().unwrap_or(payer.as_ref()),
+                collection,
+                args.update_authority,
+                args.yes,
+                Some(&args.record),
+                &options,
+            )?;
+        }
+        Command::Collection(CollectionCommand::Delegate(DelegateCommand::Approve(args))) => {
+            let collection = args.collection.collection()?;
+            let delegate = args.delegate()?;
+            let payer = load_payer()?;
+            lib::approve_collection_authority(
+                &cluster,
+                payer.as_ref(),
+                collection,
+                delegate,
+                &options,
+            )?;
+        }
+        Command::Collection(CollectionCommand::Delegate(DelegateCommand::Revoke(args))) => {
+            let collection = args.collection.collection()?;
+            let delegate = args.delegate()?;
+            let payer = load_payer()?;
+            lib::revoke_collection_authority(
+                &cluster,
+                payer.as_ref(),
+                collection,
+                delegate,
+                &options,
+            )?;
+        }
+        Command::Collection(CollectionCommand::Delegate(DelegateCommand::List(args))) => {
+            let delegates = lib::list_collection_authorities(&cluster, &args.collection()?)?;
+            if delegates.is_empty() {
+                println!("No collection authority delegates.");
+            }
+            for delegate in delegates {
+                println!("{} (record {})", delegate.delegate, delegate.record);
+            }
+        }
+        Command::MasterEdition(MasterEditionCommand::Create(args)) => {
+            let mint = args.mint.mint()?;
+            let payer = load_payer()?;
+            lib::update_nfts_collection(
+                &cluster,
+                payer.as_ref(),
+                mint,
+                metadata_pda(&mint),
+                args.max_supply,
+                &options,
+            )?;
+        }
+        Command::MasterEdition(MasterEditionCommand::Print(args)) => {
+            let master = args.master.mint()?;
+            let payer = load_payer()?;
+            let mint = load_mint(&args.mint_keypair)?;
+            let printed = lib::print_edition(
+                &cluster,
+                payer.as_ref(),
+                master,
+                mint.as_ref(),
+                &args.owner.unwrap_or(payer.pubkey()),
+                args.edition,
+                &options,
+            )?;
+            println!("Edition number: {}", printed.edition);
+        }
+        Command::MasterEdition(MasterEditionCommand::Editions(args)) => {
+            print!("{}", lib::taken_editions(&cluster, args.mint()?)?);
+        }
+        Command::Token(TokenCommand::Create(args)) => {
+            let spec = lib::MetadataSpec::from_file(args.spec()?)?;
+            let supply = args.base_units()?;
+            let payer = load_payer()?;
+            let mint = load_mint(&args.mint_keypair)?;
+            lib::create_fungible_token(
+                &cluster,
+                payer.as_ref(),
+                mint.as_ref(),
+                args.decimals,
+                supply,
+                &spec,
+                &options,
+            )?;
+        }
+        Command::Nft(NftCommand::Mint(args)) => {
+            let spec = lib::MetadataSpec::from_file(args.spec()?)?;
+            let payer = load_payer()?;
+            let mint = load_mint(&args.mint_keypair)?;
+            lib::mint_nft(
+                &cluster,
+                payer.as_ref(),
+                mint.as_ref(),
+                &args.owner.unwrap_or(payer.pubkey()),
+                &spec,
+                args.max_supply,
+                args.verify_collection,
+                &options,
+            )?;
+        }
+        Command::Escrow(command) => run_escrow(&cluster, &signer_source, command, &options)?,
+        Command::Keypair(_) => unreachable!("handled before the cluster is resolved"),
     }
 
     Ok(())
 }
+
+/// Escrow errors come back named, e.g. "signer is not the escrow admin",
+/// since every call knows the program id.
+fn run_escrow(
+    cluster: &lib::ClusterConfig,
+    signer_source: &lib::SignerSource,
+    command: EscrowCommand,
+    options: &lib::TxOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Admin (or depositor) and payer, the former defaulting to the latter
+    let load_signers = |args: &cli::EscrowSignerArgs| -> Result<_, lib::MetaLootError> {
+        let payer = lib::resolve_signer(signer_source)?;
+        let authority = args
+            .authority
+            .as_ref()
+            .map(lib::resolve_signer)
+            .transpose()?;
+        Ok((payer, authority))
+    };
+    let pause = |args: cli::EscrowSignerArgs, paused: bool| -> Result<(), lib::MetaLootError> {
+        let (payer, admin) = load_signers(&args)?;
+        lib::pause_escrow(
+            cluster,
+            payer.as_ref(),
+            admin.as_deref().unwrap_or(payer.as_ref()),
+            &args.program.program_id,
+            paused,
+            options,
+        )?;
+        Ok(())
+    };
+
+    match command {
+        EscrowCommand::Init(args) => {
+            let (payer, admin) = load_signers(&args.signer)?;
+            lib::initialize_escrow(
+                cluster,
+                payer.as_ref(),
+                admin.as_deref().unwrap_or(payer.as_ref()),
+                &args.signer.program.program_id,
+                &args.mint,
+                options,
+            )?;
+        }
+        EscrowCommand::Deposit(args) => {
+            let (payer, depositor) = load_signers(&args.signer)?;
+            lib::deposit_to_escrow(
+                cluster,
+                payer.as_ref(),
+                depositor.as_deref().unwrap_or(payer.as_ref()),
+                &args.signer.program.program_id,
+                args.amount,
+                options,
+            )?;
+        }
+        EscrowCommand::Withdraw(args) => {
+            let (payer, admin) = load_signers(&args.signer)?;
+            lib::withdraw_from_escrow(
+                cluster,
+                payer.as_ref(),
+                admin.as_deref().unwrap_or(payer.as_ref()),
+                &args.signer.program.program_id,
+                &args.wallet.unwrap_or(payer.pubkey()),
+                args.amount,
+                options,
+            )?;
+        }
+        EscrowCommand::Claim(args) => {
+            let (payer, admin) = load_signers(&args.signer)?;
+            lib::claim_from_escrow(
+                cluster,
+                payer.as_ref(),
+                admin.as_deref().unwrap_or(payer.as_ref()),
+                &args.signer.program.program_id,
+                &args.user,
+                args.amount,
+                args.claim_id,
+                options,
+            )?;
+        }
+        EscrowCommand::Claimed(args) => {
+            match lib::fetch_claim_receipt(
+                cluster,
+                &args.program.program_id,
+                &args.user,
+                args.claim_id,
+            )? {
+                Some(receipt) => println!(
+                    "Claim {} paid: {} base units to {}",
+                    receipt.claim_id, receipt.amount, receipt.user
+                ),
+                None => println!("Claim {} not paid to {}", args.claim_id, args.user),
+            }
+        }
+        EscrowCommand::SetAdmin(args) => {
+            let (payer, admin) = load_signers(&args.signer)?;
+            lib::set_escrow_admin(
+                cluster,
+                payer.as_ref(),
+                admin.as_deref().unwrap_or(payer.as_ref()),
+                &args.signer.program.program_id,
+                &args.new_admin,
+                options,
+            )?;
+        }
+        EscrowCommand::Pause(args) => pause(args, true)?,
+        EscrowCommand::Resume(args) => pause(args, false)?,
+        EscrowCommand::Close(args) => {
+            let (payer, admin) = load_signers(&args.signer)?;
+            lib::close_escrow(
+                cluster,
+                payer.as_ref(),
+                admin.as_deref().unwrap_or(payer.as_ref()),
+                &args.signer.program.program_id,
+                &args.wallet.unwrap_or(payer.pubkey()),
+                options,
+            )?;
+        }
+        EscrowCommand::Show(args) => {
+            let config = lib::fetch_escrow_config(cluster, &args.program_id)?;
+            println!("Admin:  {}", config.admin);
+            println!("Mint:   {}", config.mint);
+            println!("Escrow: {}", config.escrow);
+            println!("Paused: {}", config.paused);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use crate::{cluster::ClusterConfig, error::MetaLootError, tx::SimulationReport};
+use solana_sdk::{
+    hash::Hash, instruction::Instruction, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey,
+};
+use std::{
+    io::{self, Write},
+    str::FromStr,
+};
+
+/// Genesis hash of mainnet-beta; matching on it catches mainnet behind any
+/// RPC URL, private providers included.
+pub const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+
+/// What has to be typed to go ahead on mainnet.
+pub const MAINNET_CONFIRMATION: &str = "mainnet";
+
+/// Describes a transaction for what gets printed, and lists the effects that
+/// cannot be undone once it lands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Action {
+    pub description: String,
+    pub irreversible: Vec<String>,
+}
+
+impl Action {
+    pub fn new(description: impl Into<String>) -> Self {
+        Action {
+            description: description.into(),
+            irreversible: Vec::new(),
+        }
+    }
+
+    pub fn irreversible(mut self, effect: impl Into<String>) -> Self {
+        self.irreversible.push(effect.into());
+        self
+    }
+
+    pub fn irreversible_if(self, condition: bool, effect: impl Into<String>) -> Self {
+        if condition {
+            self.irreversible(effect)
+        } else {
+            self
+        }
+    }
+}
+
+impl From<&str> for Action {
+    fn from(description: &str) -> Self {
+        Action::new(description)
+    }
+}
+
+pub fn is_mainnet(cluster: &ClusterConfig) -> Result<bool, MetaLootError> {
+    let mainnet = Hash::from_str(MAINNET_GENESIS_HASH).expect("valid genesis hash");
+    Ok(cluster.client().get_genesis_hash()? == mainnet)
+}
+
+/// Prints what a mainnet transaction will touch and cost, then asks for the
+/// confirmation word unless `assume_yes` is set.
+pub fn confirm_mainnet(
+    action: &Action,
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    report: &SimulationReport,
+    assume_yes: bool,
+) -> Result<(), MetaLootError> {
+    println!(
+        "You are about to send a MAINNET transaction: {}",
+        action.description
+    );
+    println!("Accounts touched:");
+    let mut seen = Vec::new();
+    for meta in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if seen.contains(&meta.pubkey) {
+            continue;
+        }
+        seen.push(meta.pubkey);
+        let access = if meta.is_writable {
+            "writable"
+        } else {
+            "read-only"
+        };
+        println!("  {} ({})", meta.pubkey, access);
+    }
+    println!(
+        "Estimated cost: {} SOL (fee {} lamports, plus rent)",
+        report.payer_cost(payer) as f64 / LAMPORTS_PER_SOL as f64,
+        report.fee
+    );
+    if action.irreversible.is_empty() {
+        println!("Irreversible effects: none");
+    } else {
+        println!("Irreversible effects:");
+        for effect in &action.irreversible {
+            println!("  - {}", effect);
+        }
+    }
+
+    if assume_yes {
+        println!("Confirmed by --yes-mainnet.");
+        return Ok(());
+    }
+
+    confirm_typed(MAINNET_CONFIRMATION, "mainnet transaction")
+}
+
+/// Asks for `word` to be typed back, failing with `Aborted` on anything else.
+/// `what` names the thing being confirmed in that error.
+pub fn confirm_typed(word: &str, what: &str) -> Result<(), MetaLootError> {
+    print!("Type '{}' to continue: ", word);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if answer.trim() == word {
+        Ok(())
+    } else {
+        Err(MetaLootError::Aborted(format!(
+            "{} was not confirmed",
+            what
+        )))
+    }
+}
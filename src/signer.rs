@@ -0,0 +1,160 @@
+use crate::error::MetaLootError;
+use serde::Deserialize;
+use solana_sdk::{
+    derivation_path::DerivationPath,
+    signer::{
+        keypair::{
+            generate_seed_from_seed_phrase_and_passphrase, keypair_from_seed_and_derivation_path,
+            keypair_from_seed_phrase_and_passphrase, read_keypair, read_keypair_file, Keypair,
+        },
+        Signer,
+    },
+};
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Env var consulted when no keypair source is given explicitly.
+pub const KEYPAIR_ENV: &str = "METALOOT_KEYPAIR";
+/// Env vars holding the BIP39 words (and optional passphrase) for `seed-phrase`.
+pub const SEED_PHRASE_ENV: &str = "METALOOT_SEED_PHRASE";
+pub const SEED_PASSPHRASE_ENV: &str = "METALOOT_SEED_PASSPHRASE";
+
+/// Where the payer keypair comes from.
+///
+/// Parsed from the same strings the CLI accepts for `--keypair`: `stdin`,
+/// `config`, `env:<VAR>`, `seed-phrase`, or anything else as a file path.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignerSource {
+    /// A Solana CLI JSON keypair file.
+    File(PathBuf),
+    /// The `keypair_path` of the Solana CLI `config.yml`.
+    CliConfig,
+    /// An env var holding a JSON byte array or a base58 secret key.
+    Env(String),
+    /// BIP39 words read from `METALOOT_SEED_PHRASE`, or typed on stdin when unset.
+    /// Without a derivation path this matches `solana-keygen recover prompt://`.
+    SeedPhrase(Option<DerivationPath>),
+    /// A JSON keypair piped on stdin.
+    Stdin,
+}
+
+impl Default for SignerSource {
+    /// `METALOOT_KEYPAIR` when set, otherwise the Solana CLI default keypair.
+    fn default() -> Self {
+        match env::var_os(KEYPAIR_ENV) {
+            Some(_) => SignerSource::Env(KEYPAIR_ENV.to_string()),
+            None => SignerSource::CliConfig,
+        }
+    }
+}
+
+impl FromStr for SignerSource {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Ok(match source {
+            "stdin" => SignerSource::Stdin,
+            "config" => SignerSource::CliConfig,
+            "seed-phrase" => SignerSource::SeedPhrase(None),
+            _ => match source.strip_prefix("env:") {
+                Some("") => return Err("env: needs a variable name".to_string()),
+                Some(var) => SignerSource::Env(var.to_string()),
+                None => SignerSource::File(PathBuf::from(source)),
+            },
+        })
+    }
+}
+
+impl SignerSource {
+    /// Attaches a derivation path (`<account>/<change>`, e.g. `0/0`) to a
+    /// seed-phrase source; any other source rejects it.
+    pub fn with_derivation_path(self, path: Option<&str>) -> Result<Self, MetaLootError> {
+        match (self, path) {
+            (source, None) => Ok(source),
+            (SignerSource::SeedPhrase(_), Some(path)) => Ok(SignerSource::SeedPhrase(Some(
+                DerivationPath::from_key_str(path).map_err(keypair_error)?,
+            ))),
+            (source, Some(_)) => Err(MetaLootError::InvalidInput(format!(
+                "a derivation path only applies to seed phrases, not {:?}",
+                source
+            ))),
+        }
+    }
+}
+
+fn keypair_error(err: impl ToString) -> MetaLootError {
+    MetaLootError::Keypair(err.to_string())
+}
+
+/// The subset of the Solana CLI `config.yml` we care about.
+#[derive(Deserialize)]
+struct CliConfig {
+    keypair_path: PathBuf,
+}
+
+/// Keypair path from `~/.config/solana/cli/config.yml`, falling back to
+/// `~/.config/solana/id.json` like the Solana CLI does when no config exists.
+fn cli_config_keypair() -> Result<PathBuf, MetaLootError> {
+    let home = env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .ok_or_else(|| keypair_error("could not locate the home directory"))?;
+    let solana_dir = Path::new(&home).join(".config").join("solana");
+    let config_file = solana_dir.join("cli").join("config.yml");
+    if !config_file.exists() {
+        return Ok(solana_dir.join("id.json"));
+    }
+    let config: CliConfig = serde_yaml::from_str(&fs::read_to_string(&config_file)?)
+        .map_err(|err| keypair_error(format!("{}: {}", config_file.display(), err)))?;
+    Ok(config.keypair_path)
+}
+
+/// Resolves the source to the underlying keypair. Only needed where the secret
+/// itself is required; everything else should go through `resolve_signer`.
+pub fn resolve_keypair(source: &SignerSource) -> Result<Keypair, MetaLootError> {
+    match source {
+        SignerSource::File(path) => read_keypair_file(path)
+            .map_err(|err| keypair_error(format!("{}: {}", path.display(), err))),
+        SignerSource::CliConfig => resolve_keypair(&SignerSource::File(cli_config_keypair()?)),
+        SignerSource::Env(var) => {
+            let value = env::var(var).map_err(|err| keypair_error(format!("{}: {}", var, err)))?;
+            let value = value.trim();
+            if value.starts_with('[') {
+                read_keypair(&mut value.as_bytes()).map_err(keypair_error)
+            } else {
+                let bytes = bs58::decode(value).into_vec().map_err(keypair_error)?;
+                Keypair::try_from(bytes.as_slice()).map_err(keypair_error)
+            }
+        }
+        SignerSource::SeedPhrase(derivation_path) => {
+            let phrase = match env::var(SEED_PHRASE_ENV) {
+                Ok(phrase) => phrase,
+                Err(_) => {
+                    print!("Seed phrase: ");
+                    io::stdout().flush()?;
+                    let mut phrase = String::new();
+                    io::stdin().read_line(&mut phrase)?;
+                    phrase
+                }
+            };
+            let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+            let passphrase = env::var(SEED_PASSPHRASE_ENV).unwrap_or_default();
+            match derivation_path {
+                Some(path) => keypair_from_seed_and_derivation_path(
+                    &generate_seed_from_seed_phrase_and_passphrase(&phrase, &passphrase),
+                    Some(path.clone()),
+                ),
+                None => keypair_from_seed_phrase_and_passphrase(&phrase, &passphrase),
+            }
+            .map_err(keypair_error)
+        }
+        SignerSource::Stdin => read_keypair(&mut io::stdin()).map_err(keypair_error),
+    }
+}
+
+pub fn resolve_signer(source: &SignerSource) -> Result<Box<dyn Signer>, MetaLootError> {
+    Ok(Box::new(resolve_keypair(source)?))
+}
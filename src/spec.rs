@@ -0,0 +1,278 @@
+use crate::error::MetaLootError;
+use mpl_token_metadata::types::{Collection, CollectionDetails, Creator, DataV2, Uses};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use solana_sdk::pubkey::Pubkey;
+use std::{fs, path::Path};
+
+/// Everything needed to build a `DataV2` for a token, so new assets can be
+/// described in a TOML/JSON file instead of being compiled into the binary.
+/// Unknown fields are rejected so a misspelt one is not silently dropped.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetadataSpec {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    /// Seller fee in basis points (500 = 5%).
+    #[serde(default, alias = "seller_fee_basis_points")]
+    pub royalty: u16,
+    /// Left empty, the payer becomes the sole (verified) creator.
+    #[serde(default)]
+    pub creators: Vec<CreatorSpec>,
+    /// Mint address of the parent collection, always written unverified.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub collection: Option<Pubkey>,
+    #[serde(default)]
+    pub uses: Option<Uses>,
+    #[serde(default = "default_is_mutable")]
+    pub is_mutable: bool,
+    #[serde(default)]
+    pub collection_details: Option<CollectionDetails>,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreatorSpec {
+    #[serde_as(as = "DisplayFromStr")]
+    pub address: Pubkey,
+    pub share: u8,
+}
+
+fn default_is_mutable() -> bool {
+    true
+}
+
+impl MetadataSpec {
+    /// Loads a spec from disk; `.toml` files are parsed as TOML, anything else as JSON.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, MetaLootError> {
+        let path = path.as_ref();
+        let spec_error = |message: String| MetaLootError::Spec {
+            path: path.to_path_buf(),
+            message,
+        };
+        let contents = fs::read_to_string(path).map_err(|err| spec_error(err.to_string()))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents).map_err(|err| spec_error(err.to_string())),
+            _ => serde_json::from_str(&contents).map_err(|err| spec_error(err.to_string())),
+        }
+    }
+
+    /// Only the signing payer can be marked verified; any other creator has to
+    /// sign its own verification later.
+    pub fn creators(&self, payer: &Pubkey) -> Vec<Creator> {
+        if self.creators.is_empty() {
+            return vec![Creator {
+                address: *payer,
+                verified: true,
+                share: 100,
+            }];
+        }
+        self.creators
+            .iter()
+            .map(|creator| Creator {
+                address: creator.address,
+                verified: creator.address == *payer,
+                share: creator.share,
+            })
+            .collect()
+    }
+
+    pub fn data_v2(&self, payer: &Pubkey) -> DataV2 {
+        DataV2 {
+            name: self.name.clone(),
+            symbol: self.symbol.clone(),
+            uri: self.uri.clone(),
+            seller_fee_basis_points: self.royalty,
+            creators: Some(self.creators(payer)),
+            collection: self.collection.map(|key| Collection {
+                verified: false,
+                key,
+            }),
+            uses: self.uses.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const MINIMAL: &str = "name = \"MetaLoot\"\nsymbol = \"MLT\"\nuri = \"https://example.com\"\n";
+
+    /// Writes `contents` to a fresh file named `name` and loads it as a spec.
+    fn load(name: &str, contents: &str) -> Result<MetadataSpec, MetaLootError> {
+        let dir = std::env::temp_dir().join(format!("metaloot-spec-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, contents).unwrap();
+        MetadataSpec::from_file(&path)
+    }
+
+    fn spec_error(result: Result<MetadataSpec, MetaLootError>) -> String {
+        match result {
+            Err(MetaLootError::Spec { message, .. }) => message,
+            other => panic!("expected a spec error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_toml() {
+        let creator = Pubkey::new_unique();
+        let spec = load(
+            "full.toml",
+            &format!(
+                r#"
+                name = "MetaLoot"
+                symbol = "MLT"
+                uri = "https://example.com/metaloot.json"
+                royalty = 500
+                is_mutable = false
+
+                [[creators]]
+                address = "{}"
+                share = 100
+                "#,
+                creator
+            ),
+        )
+        .unwrap();
+        assert_eq!(spec.name, "MetaLoot");
+        assert_eq!(spec.royalty, 500);
+        assert!(!spec.is_mutable);
+        assert_eq!(
+            spec.creators,
+            vec![CreatorSpec {
+                address: creator,
+                share: 100
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_json_with_the_metaplex_royalty_name() {
+        let collection = Pubkey::new_unique();
+        let spec = load(
+            "full.json",
+            &format!(
+                r#"{{
+                    "name": "MetaLoot",
+                    "symbol": "MLT",
+                    "uri": "https://example.com/metaloot.json",
+                    "seller_fee_basis_points": 250,
+                    "collection": "{}"
+                }}"#,
+                collection
+            ),
+        )
+        .unwrap();
+        assert_eq!(spec.royalty, 250);
+        assert_eq!(spec.collection, Some(collection));
+    }
+
+    #[test]
+    fn optional_fields_default() {
+        let spec = load("minimal.toml", MINIMAL).unwrap();
+        assert_eq!(spec.royalty, 0);
+        assert!(spec.creators.is_empty());
+        assert_eq!(spec.collection, None);
+        assert_eq!(spec.uses, None);
+        assert!(spec.is_mutable);
+        assert_eq!(spec.collection_details, None);
+    }
+
+    #[test]
+    fn missing_required_field_fails() {
+        let message = spec_error(load(
+            "no-uri.toml",
+            "name = \"MetaLoot\"\nsymbol = \"MLT\"\n",
+        ));
+        assert!(message.contains("uri"), "{}", message);
+    }
+
+    #[test]
+    fn unknown_field_fails() {
+        let message = spec_error(load(
+            "typo.json",
+            r#"{"name": "MetaLoot", "symbol": "MLT", "uri": "https://example.com", "royalti": 500}"#,
+        ));
+        assert!(message.contains("royalti"), "{}", message);
+    }
+
+    #[test]
+    fn missing_file_fails() {
+        let path = PathBuf::from("specs/does-not-exist.toml");
+        assert!(matches!(
+            MetadataSpec::from_file(&path),
+            Err(MetaLootError::Spec { path: failed, .. }) if failed == path
+        ));
+    }
+
+    #[test]
+    fn payer_becomes_sole_verified_creator_when_none_are_listed() {
+        let spec = load("no-creators.toml", MINIMAL).unwrap();
+        let payer = Pubkey::new_unique();
+        let expected = vec![Creator {
+            address: payer,
+            verified: true,
+            share: 100,
+        }];
+        assert_eq!(spec.creators(&payer), expected);
+        assert_eq!(spec.data_v2(&payer).creators, Some(expected));
+    }
+
+    #[test]
+    fn only_the_payer_is_verified_among_listed_creators() {
+        let payer = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let spec = MetadataSpec {
+            creators: vec![
+                CreatorSpec {
+                    address: other,
+                    share: 60,
+                },
+                CreatorSpec {
+                    address: payer,
+                    share: 40,
+                },
+            ],
+            collection: Some(Pubkey::new_unique()),
+            ..load("listed.toml", MINIMAL).unwrap()
+        };
+        let data = spec.data_v2(&payer);
+        let creators = data.creators.unwrap();
+        assert_eq!(
+            creators
+                .iter()
+                .map(|c| (c.address, c.verified, c.share))
+                .collect::<Vec<_>>(),
+            vec![(other, false, 60), (payer, true, 40)]
+        );
+        // Collections are always written unverified
+        assert_eq!(
+            data.collection,
+            Some(Collection {
+                verified: false,
+                key: spec.collection.unwrap()
+            })
+        );
+    }
+
+    #[test]
+    fn shipped_specs_load() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("specs");
+        let mut loaded = 0;
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if let Err(err) = MetadataSpec::from_file(&path) {
+                panic!("{}", err);
+            }
+            loaded += 1;
+        }
+        assert!(loaded > 0);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{
+    cluster::ClusterConfig,
+    collection::{self, VerifyMethod},
+    create_master_edition_instruction, create_metadata_instruction,
+    error::MetaLootError,
+    find_master_edition_pda, find_metadata_pda, inspect, master_edition_action, metadata_action,
+    spec::MetadataSpec,
+    tx, TxOptions, TxOutcome,
+};
+use solana_sdk::{instruction::Instruction, program_pack::Pack, pubkey::Pubkey, signer::Signer};
+use solana_system_interface::instruction as system_instruction;
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use spl_token::state::Mint;
+
+/// Accounts created by `create_fungible_token`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FungibleToken {
+    pub mint: Pubkey,
+    /// The payer's associated token account, holding the initial supply.
+    pub token_account: Pubkey,
+    pub metadata_pda: Pubkey,
+    pub outcome: TxOutcome,
+}
+
+/// Accounts created by `mint_nft`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MintedNft {
+    pub mint: Pubkey,
+    /// The owner's associated token account, holding the single token.
+    pub token_account: Pubkey,
+    pub metadata_pda: Pubkey,
+    pub master_edition_pda: Pubkey,
+    /// One outcome per transaction; a single one unless the mint had to be split.
+    pub outcomes: Vec<TxOutcome>,
+}
+
+/// Instructions that create and initialize a new mint account with `payer` as
+/// its mint authority.
+pub fn create_mint_instructions(
+    cluster: &ClusterConfig,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    decimals: u8,
+    freeze_authority: Option<&Pubkey>,
+) -> Result<Vec<Instruction>, MetaLootError> {
+    let rent = cluster
+        .client()
+        .get_minimum_balance_for_rent_exemption(Mint::LEN)?;
+    Ok(vec![
+        system_instruction::create_account(payer, mint, rent, Mint::LEN as u64, &spl_token::ID),
+        spl_token::instruction::initialize_mint2(
+            &spl_token::ID,
+            mint,
+            payer,
+            freeze_authority,
+            decimals,
+        )
+        .map_err(|err| MetaLootError::InvalidInput(err.to_string()))?,
+    ])
+}
+
+/// Creates a fungible token in a single transaction: the mint with `decimals`,
+/// the payer's associated token account, `initial_supply` base units minted
+/// into it and the metadata account described by `spec`.
+///
+/// `mint` must be a fresh keypair; it signs the account creation.
+pub fn create_fungible_token(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    mint: &dyn Signer,
+    decimals: u8,
+    initial_supply: u64,
+    spec: &MetadataSpec,
+    options: &TxOptions,
+) -> Result<FungibleToken, MetaLootError> {
+    let payer_key = payer.pubkey();
+    let mint_key = mint.pubkey();
+    let token_account = get_associated_token_address(&payer_key, &mint_key);
+    let metadata_pda = find_metadata_pda(&mint_key);
+
+    // Built first so a bad spec fails before any RPC call
+    let metadata_instruction =
+        create_metadata_instruction(&payer_key, mint_key, metadata_pda, spec)?;
+
+    let mut instructions =
+        create_mint_instructions(cluster, &payer_key, &mint_key, decimals, None)?;
+    instructions.push(create_associated_token_account_idempotent(
+        &payer_key,
+        &payer_key,
+        &mint_key,
+        &spl_token::ID,
+    ));
+    if initial_supply > 0 {
+        instructions.push(mint_to(
+            &mint_key,
+            &token_account,
+            &payer_key,
+            initial_supply,
+        )?);
+    }
+    instructions.push(metadata_instruction);
+
+    println!("Mint: {}", mint_key);
+    println!("Token account: {}", token_account);
+    let outcome = tx::submit(
+        cluster,
+        payer,
+        &[mint],
+        &instructions,
+        options,
+        metadata_action("Fungible token created", spec),
+    )?;
+
+    Ok(FungibleToken {
+        mint: mint_key,
+        token_account,
+        metadata_pda,
+        outcome,
+    })
+}
+
+pub(crate) fn mint_to(
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, MetaLootError> {
+    spl_token::instruction::mint_to(&spl_token::ID, mint, token_account, authority, &[], amount)
+        .map_err(|err| MetaLootError::InvalidInput(err.to_string()))
+}
+
+/// Mints a single NFT to `owner`: a 0-decimal mint, the owner's associated
+/// token account holding one token, the metadata from `spec` and a master
+/// edition allowing `max_supply` prints (`None` for unlimited).
+///
+/// When `verify_collection` is set and the spec names a collection, the item
+/// is also verified into it; the payer must be the collection's update
+/// authority. Everything goes out in one transaction when it fits, otherwise
+/// it is split into as few as needed, in order.
+#[allow(clippy::too_many_arguments)]
+pub fn mint_nft(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    mint: &dyn Signer,
+    owner: &Pubkey,
+    spec: &MetadataSpec,
+    max_supply: Option<u64>,
+    verify_collection: bool,
+    options: &TxOptions,
+) -> Result<MintedNft, MetaLootError> {
+    let payer_key = payer.pubkey();
+    let mint_key = mint.pubkey();
+    let token_account = get_associated_token_address(owner, &mint_key);
+    let metadata_pda = find_metadata_pda(&mint_key);
+    let master_edition_pda = find_master_edition_pda(&mint_key);
+
+    let metadata_instruction =
+        create_metadata_instruction(&payer_key, mint_key, metadata_pda, spec)?;
+
+    let verify_instruction = match (spec.collection, verify_collection) {
+        (Some(collection_mint), true) => {
+            let collection =
+                inspect::fetch_decoded_metadata(cluster, &find_metadata_pda(&collection_mint))?;
+            let authority_record =
+                collection::collection_authority_record(cluster, &collection, &payer_key)?;
+            // The new item already names the collection and is not programmable
+            let method = if collection.collection_details.is_some() {
+                VerifyMethod::VerifySized
+            } else {
+                VerifyMethod::Verify
+            };
+            Some(collection::verify_instruction(
+                method,
+                &payer_key,
+                authority_record,
+                &payer_key,
+                &mint_key,
+                &payer_key,
+                &collection_mint,
+            ))
+        }
+        _ => None,
+    };
+
+    // The freeze authority moves to the master edition along with the mint authority
+    let mut mint_group =
+        create_mint_instructions(cluster, &payer_key, &mint_key, 0, Some(&payer_key))?;
+    mint_group.push(create_associated_token_account_idempotent(
+        &payer_key,
+        owner,
+        &mint_key,
+        &spl_token::ID,
+    ));
+    mint_group.push(mint_to(&mint_key, &token_account, &payer_key, 1)?);
+    let mut groups = vec![
+        mint_group,
+        vec![
+            metadata_instruction,
+            create_master_edition_instruction(&payer_key, mint_key, metadata_pda, max_supply),
+        ],
+    ];
+    groups.extend(verify_instruction.map(|instruction| vec![instruction]));
+
+    let batches = tx::pack_instructions(&payer_key, groups);
+    if batches.len() > 1 {
+        println!(
+            "Too large for one transaction, sending {} in order; a failure part way leaves the earlier ones applied.",
+            batches.len()
+        );
+    }
+
+    println!("Mint: {}", mint_key);
+    println!("Token account: {}", token_account);
+    let mut outcomes = Vec::new();
+    for (index, batch) in batches.iter().enumerate() {
+        let description = if batches.len() > 1 {
+            format!("NFT minted ({}/{})", index + 1, batches.len())
+        } else {
+            "NFT minted".to_string()
+        };
+        if options.dry_run && index > 0 {
+            // Later transactions depend on accounts the earlier ones create
+            println!(
+                "Dry run, not simulated: {} depends on the previous transaction",
+                description
+            );
+            continue;
+        }
+        let mut action = metadata_action(&description, spec);
+        action
+            .irreversible
+            .extend(master_edition_action(&description).irreversible);
+        outcomes.push(tx::submit(cluster, payer, &[mint], batch, options, action)?);
+    }
+
+    Ok(MintedNft {
+        mint: mint_key,
+        token_account,
+        metadata_pda,
+        master_edition_pda,
+        outcomes,
+    })
+}
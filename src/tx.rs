@@ -0,0 +1,325 @@
+use crate::{
+    cluster::ClusterConfig,
+    error::MetaLootError,
+    safety::{self, Action},
+};
+use solana_client::{
+    pubsub_client::PubsubClient,
+    rpc_config::{
+        RpcSignatureSubscribeConfig, RpcSimulateTransactionAccountsConfig,
+        RpcSimulateTransactionConfig,
+    },
+    rpc_response::RpcSignatureResult,
+};
+use solana_sdk::{
+    instruction::Instruction, message::Message, packet::PACKET_DATA_SIZE, pubkey::Pubkey,
+    signature::Signature, signer::Signer, transaction::Transaction,
+};
+use std::fmt;
+
+/// How a signed transaction is handled. Every function that sends a
+/// transaction takes these.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxOptions {
+    /// Simulate the transaction and report what it would do instead of sending it.
+    pub dry_run: bool,
+    /// Skip the typed confirmation on mainnet; the summary is still printed.
+    pub yes_mainnet: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TxOutcome {
+    Sent(Signature),
+    Simulated(SimulationReport),
+}
+
+impl TxOutcome {
+    pub fn signature(&self) -> Option<&Signature> {
+        match self {
+            TxOutcome::Sent(signature) => Some(signature),
+            TxOutcome::Simulated(_) => None,
+        }
+    }
+}
+
+/// What a transaction would have done, from `simulateTransaction`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationReport {
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    /// Signature fee in lamports.
+    pub fee: u64,
+    /// Lamport balances of every writable account before and after.
+    pub balance_changes: Vec<BalanceChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceChange {
+    pub account: Pubkey,
+    pub before: u64,
+    pub after: u64,
+}
+
+impl BalanceChange {
+    pub fn delta(&self) -> i128 {
+        self.after as i128 - self.before as i128
+    }
+}
+
+impl SimulationReport {
+    /// Lamports the transaction costs the payer, fee and rent included.
+    pub fn payer_cost(&self, payer: &Pubkey) -> i128 {
+        self.balance_changes
+            .iter()
+            .find(|change| change.account == *payer)
+            .map_or(0, |change| -change.delta())
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Logs:")?;
+        for log in &self.logs {
+            writeln!(f, "  {}", log)?;
+        }
+        match self.units_consumed {
+            Some(units) => writeln!(f, "Compute units consumed: {}", units)?,
+            None => writeln!(f, "Compute units consumed: unknown")?,
+        }
+        writeln!(f, "Fee: {} lamports", self.fee)?;
+        writeln!(f, "Balance changes:")?;
+        for change in &self.balance_changes {
+            writeln!(
+                f,
+                "  {} {} -> {} ({:+} lamports)",
+                change.account,
+                change.before,
+                change.after,
+                change.delta()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Payer first, then every other account an instruction writes to.
+fn writable_accounts(payer: &Pubkey, instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts = vec![*payer];
+    for meta in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if meta.is_writable && !accounts.contains(&meta.pubkey) {
+            accounts.push(meta.pubkey);
+        }
+    }
+    accounts
+}
+
+/// Simulates a signed transaction and collects its logs, compute units, fee
+/// and the lamport changes of its writable accounts.
+pub fn simulate(
+    cluster: &ClusterConfig,
+    transaction: &Transaction,
+    instructions: &[Instruction],
+) -> Result<SimulationReport, MetaLootError> {
+    let client = cluster.client();
+    let payer = transaction.message.account_keys[0];
+    let accounts = writable_accounts(&payer, instructions);
+    let before = client.get_multiple_accounts(&accounts)?;
+
+    let result = client
+        .simulate_transaction_with_config(
+            transaction,
+            RpcSimulateTransactionConfig {
+                sig_verify: true,
+                accounts: Some(RpcSimulateTransactionAccountsConfig {
+                    encoding: None,
+                    addresses: accounts.iter().map(Pubkey::to_string).collect(),
+                }),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )?
+        .value;
+    let logs = result.logs.unwrap_or_default();
+    if let Some(err) = result.err {
+        for log in &logs {
+            println!("  {}", log);
+        }
+        return Err(MetaLootError::from_transaction_error(err, instructions));
+    }
+
+    let after = result.accounts.unwrap_or_default();
+    let balance_changes = accounts
+        .iter()
+        .enumerate()
+        .map(|(index, account)| BalanceChange {
+            account: *account,
+            before: before[index].as_ref().map_or(0, |account| account.lamports),
+            after: after
+                .get(index)
+                .and_then(Option::as_ref)
+                .map_or(0, |account| account.lamports),
+        })
+        .collect();
+
+    Ok(SimulationReport {
+        logs,
+        units_consumed: result.units_consumed,
+        fee: client.get_fee_for_message(&transaction.message)?,
+        balance_changes,
+    })
+}
+
+/// Serialized size in bytes of a transaction carrying `instructions`,
+/// signatures included.
+pub fn transaction_size(payer: &Pubkey, instructions: &[Instruction]) -> usize {
+    let message = Message::new(instructions, Some(payer));
+    let signatures = message.header.num_required_signatures as usize;
+    // Signature count is a compact-u16, one byte below 128
+    1 + signatures * 64 + message.serialize().len()
+}
+
+/// Packs instruction groups into as few transactions as fit in a packet,
+/// keeping each group together and in order. A group that is too large on its
+/// own still gets a transaction of its own and fails when sent.
+pub fn pack_instructions(payer: &Pubkey, groups: Vec<Vec<Instruction>>) -> Vec<Vec<Instruction>> {
+    let mut batches: Vec<Vec<Instruction>> = Vec::new();
+    for group in groups {
+        if let Some(batch) = batches.last_mut() {
+            let mut candidate = batch.clone();
+            candidate.extend(group.iter().cloned());
+            if transaction_size(payer, &candidate) <= PACKET_DATA_SIZE {
+                *batch = candidate;
+                continue;
+            }
+        }
+        batches.push(group);
+    }
+    batches
+}
+
+/// Signs `instructions` with the payer plus any extra `signers`, then sends and
+/// confirms them, or only simulates them when `options.dry_run` is set.
+/// `action` describes the transaction in what gets printed, e.g.
+/// "Metadata account created", and lists what it does that cannot be undone.
+/// On mainnet the transaction is simulated first, and only once the user
+/// confirms the summary is it re-signed with a fresh blockhash and sent.
+/// Custom program errors are decoded against the instruction that raised them.
+pub fn submit(
+    cluster: &ClusterConfig,
+    payer: &dyn Signer,
+    signers: &[&dyn Signer],
+    instructions: &[Instruction],
+    options: &TxOptions,
+    action: impl Into<Action>,
+) -> Result<TxOutcome, MetaLootError> {
+    let action = action.into();
+    let client = cluster.client();
+    // Extra signers the instructions do not need, or that repeat the payer, are
+    // left out, so one list can serve every transaction of a workflow
+    let message = Message::new(instructions, Some(&payer.pubkey()));
+    let required = &message.account_keys[..message.header.num_required_signatures as usize];
+    let mut all_signers = vec![payer];
+    all_signers.extend(
+        signers
+            .iter()
+            .filter(|signer| signer.pubkey() != payer.pubkey())
+            .filter(|signer| required.contains(&signer.pubkey())),
+    );
+
+    // Get recent blockhash
+    let blockhash = client.get_latest_blockhash()?;
+    let mut transaction = Transaction::new_unsigned(message);
+    transaction.try_sign(&all_signers, blockhash)?;
+
+    if options.dry_run {
+        let report = simulate(cluster, &transaction, instructions)?;
+        println!("Dry run, not sent: {}", action.description);
+        print!("{}", report);
+        return Ok(TxOutcome::Simulated(report));
+    }
+
+    if safety::is_mainnet(cluster)? {
+        let report = simulate(cluster, &transaction, instructions)?;
+        safety::confirm_mainnet(
+            &action,
+            &payer.pubkey(),
+            instructions,
+            &report,
+            options.yes_mainnet,
+        )?;
+        // Reading the summary can outlast the simulated blockhash
+        transaction.try_sign(&all_signers, client.get_latest_blockhash()?)?;
+    }
+
+    // Send and confirm transaction
+    let signature = send_and_confirm(cluster, &transaction, instructions)?;
+    println!("{}. Signature: {}", action.description, signature);
+    Ok(TxOutcome::Sent(signature))
+}
+
+/// Sends `transaction` and waits for its signature notification on the
+/// cluster's websocket endpoint, falling back to polling over HTTP when the
+/// websocket cannot be reached or sends nothing within the confirm timeout.
+fn send_and_confirm(
+    cluster: &ClusterConfig,
+    transaction: &Transaction,
+    instructions: &[Instruction],
+) -> Result<Signature, MetaLootError> {
+    let client = cluster.client();
+    let signature = transaction.signatures[0];
+    // Subscribe before sending so the notification cannot be missed
+    let subscription = PubsubClient::signature_subscribe(
+        &cluster.ws_url(),
+        &signature,
+        Some(RpcSignatureSubscribeConfig {
+            commitment: Some(cluster.commitment),
+            enable_received_notification: Some(false),
+        }),
+    );
+    let (mut subscription, receiver) = match subscription {
+        Ok(subscription) => subscription,
+        Err(err) => {
+            eprintln!(
+                "Websocket {} unavailable ({}), confirming over HTTP",
+                cluster.ws_url(),
+                err
+            );
+            return client
+                .send_and_confirm_transaction(transaction)
+                .map_err(|err| MetaLootError::from_client_error(err, instructions));
+        }
+    };
+
+    let result = client
+        .send_transaction(transaction)
+        .map_err(|err| MetaLootError::from_client_error(err, instructions))
+        .and_then(|_| loop {
+            match receiver.recv_timeout(cluster.confirm_timeout) {
+                Ok(response) => match response.value {
+                    RpcSignatureResult::ProcessedSignature(processed) => {
+                        break match processed.err {
+                            Some(err) => {
+                                Err(MetaLootError::from_transaction_error(err, instructions))
+                            }
+                            None => Ok(signature),
+                        };
+                    }
+                    RpcSignatureResult::ReceivedSignature(_) => continue,
+                },
+                // Socket dropped or went quiet: finish the wait over HTTP
+                Err(_) => {
+                    break client
+                        .confirm_transaction_with_spinner(
+                            &signature,
+                            &transaction.message.recent_blockhash,
+                            cluster.commitment,
+                        )
+                        .map(|_| signature)
+                        .map_err(|err| MetaLootError::from_client_error(err, instructions));
+                }
+            }
+        });
+    // The notification ends the subscription; closing the socket is best effort
+    let _ = subscription.send_unsubscribe();
+    let _ = subscription.shutdown();
+    result
+}
@@ -0,0 +1,384 @@
+use mpl_token_metadata::{
+    types::{Creator, DataV2, UseMethod, Uses},
+    MAX_CREATOR_LIMIT, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH,
+};
+use solana_sdk::pubkey::Pubkey;
+use std::{error::Error, fmt};
+
+/// Highest seller fee the Token Metadata program accepts (100%).
+pub const MAX_SELLER_FEE_BASIS_POINTS: u16 = 10_000;
+
+/// A metadata value the Token Metadata program would reject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    NameTooLong { len: usize },
+    SymbolTooLong { len: usize },
+    UriTooLong { len: usize },
+    RoyaltyTooHigh { basis_points: u16 },
+    NoCreators,
+    TooManyCreators { count: usize },
+    DuplicateCreator { address: Pubkey },
+    CreatorSharesNot100 { total: u32 },
+    UsesRemainingAboveTotal { remaining: u64, total: u64 },
+    SingleUseTotalNot1 { total: u64 },
+}
+
+impl ValidationError {
+    /// The `DataV2` field the error is about.
+    pub fn field(&self) -> &'static str {
+        match self {
+            ValidationError::NameTooLong { .. } => "name",
+            ValidationError::SymbolTooLong { .. } => "symbol",
+            ValidationError::UriTooLong { .. } => "uri",
+            ValidationError::RoyaltyTooHigh { .. } => "seller_fee_basis_points",
+            ValidationError::NoCreators
+            | ValidationError::TooManyCreators { .. }
+            | ValidationError::DuplicateCreator { .. }
+            | ValidationError::CreatorSharesNot100 { .. } => "creators",
+            ValidationError::UsesRemainingAboveTotal { .. }
+            | ValidationError::SingleUseTotalNot1 { .. } => "uses",
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.field())?;
+        match self {
+            ValidationError::NameTooLong { len } => {
+                write!(f, "{} bytes, at most {} allowed", len, MAX_NAME_LENGTH)
+            }
+            ValidationError::SymbolTooLong { len } => {
+                write!(f, "{} bytes, at most {} allowed", len, MAX_SYMBOL_LENGTH)
+            }
+            ValidationError::UriTooLong { len } => {
+                write!(f, "{} bytes, at most {} allowed", len, MAX_URI_LENGTH)
+            }
+            ValidationError::RoyaltyTooHigh { basis_points } => write!(
+                f,
+                "{} basis points, at most {} allowed",
+                basis_points, MAX_SELLER_FEE_BASIS_POINTS
+            ),
+            ValidationError::NoCreators => write!(f, "list is empty, omit it instead"),
+            ValidationError::TooManyCreators { count } => {
+                write!(
+                    f,
+                    "{} creators, at most {} allowed",
+                    count, MAX_CREATOR_LIMIT
+                )
+            }
+            ValidationError::DuplicateCreator { address } => {
+                write!(f, "{} is listed more than once", address)
+            }
+            ValidationError::CreatorSharesNot100 { total } => {
+                write!(f, "shares add up to {}, they must add up to 100", total)
+            }
+            ValidationError::UsesRemainingAboveTotal { remaining, total } => {
+                write!(f, "remaining {} is above total {}", remaining, total)
+            }
+            ValidationError::SingleUseTotalNot1 { total } => {
+                write!(f, "single use needs a total of 1, got {}", total)
+            }
+        }
+    }
+}
+
+/// Every problem found in one piece of metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationErrors(pub Vec<ValidationError>);
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid metadata")?;
+        for error in &self.0 {
+            write!(f, "\n  {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ValidationErrors {}
+
+pub fn validate_creators(creators: &[Creator]) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    if creators.is_empty() {
+        errors.push(ValidationError::NoCreators);
+        return errors;
+    }
+    if creators.len() > MAX_CREATOR_LIMIT {
+        errors.push(ValidationError::TooManyCreators {
+            count: creators.len(),
+        });
+    }
+    for (index, creator) in creators.iter().enumerate() {
+        let seen_before = creators[..index]
+            .iter()
+            .any(|other| other.address == creator.address);
+        let reported = errors.contains(&ValidationError::DuplicateCreator {
+            address: creator.address,
+        });
+        if seen_before && !reported {
+            errors.push(ValidationError::DuplicateCreator {
+                address: creator.address,
+            });
+        }
+    }
+    let total = creators.iter().map(|creator| creator.share as u32).sum();
+    if total != 100 {
+        errors.push(ValidationError::CreatorSharesNot100 { total });
+    }
+    errors
+}
+
+pub fn validate_uses(uses: &Uses) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    if uses.remaining > uses.total {
+        errors.push(ValidationError::UsesRemainingAboveTotal {
+            remaining: uses.remaining,
+            total: uses.total,
+        });
+    }
+    if uses.use_method == UseMethod::Single && uses.total != 1 {
+        errors.push(ValidationError::SingleUseTotalNot1 { total: uses.total });
+    }
+    errors
+}
+
+/// Checks `data` against the limits the Token Metadata program enforces, so
+/// bad input is caught before anything is signed or paid for.
+pub fn validate_data(data: &DataV2) -> Result<(), ValidationErrors> {
+    let mut errors = Vec::new();
+    if data.name.len() > MAX_NAME_LENGTH {
+        errors.push(ValidationError::NameTooLong {
+            len: data.name.len(),
+        });
+    }
+    if data.symbol.len() > MAX_SYMBOL_LENGTH {
+        errors.push(ValidationError::SymbolTooLong {
+            len: data.symbol.len(),
+        });
+    }
+    if data.uri.len() > MAX_URI_LENGTH {
+        errors.push(ValidationError::UriTooLong {
+            len: data.uri.len(),
+        });
+    }
+    if data.seller_fee_basis_points > MAX_SELLER_FEE_BASIS_POINTS {
+        errors.push(ValidationError::RoyaltyTooHigh {
+            basis_points: data.seller_fee_basis_points,
+        });
+    }
+    if let Some(creators) = &data.creators {
+        errors.extend(validate_creators(creators));
+    }
+    if let Some(uses) = &data.uses {
+        errors.extend(validate_uses(uses));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationErrors(errors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn creator(share: u8) -> Creator {
+        Creator {
+            address: Pubkey::new_unique(),
+            verified: false,
+            share,
+        }
+    }
+
+    fn data() -> DataV2 {
+        DataV2 {
+            name: "MetaLoot".to_string(),
+            symbol: "MLT".to_string(),
+            uri: "https://example.com/metaloot.json".to_string(),
+            seller_fee_basis_points: 500,
+            creators: Some(vec![creator(100)]),
+            collection: None,
+            uses: None,
+        }
+    }
+
+    fn uses(use_method: UseMethod, remaining: u64, total: u64) -> Uses {
+        Uses {
+            use_method,
+            remaining,
+            total,
+        }
+    }
+
+    fn errors(data: &DataV2) -> Vec<ValidationError> {
+        validate_data(data)
+            .err()
+            .map_or_else(Vec::new, |errors| errors.0)
+    }
+
+    #[test]
+    fn valid_data_passes() {
+        assert_eq!(validate_data(&data()), Ok(()));
+    }
+
+    #[test]
+    fn name_at_limit_passes_and_one_over_fails() {
+        let mut data = data();
+        data.name = "n".repeat(32);
+        assert_eq!(errors(&data), vec![]);
+        data.name = "n".repeat(33);
+        assert_eq!(
+            errors(&data),
+            vec![ValidationError::NameTooLong { len: 33 }]
+        );
+    }
+
+    #[test]
+    fn symbol_at_limit_passes_and_one_over_fails() {
+        let mut data = data();
+        data.symbol = "S".repeat(10);
+        assert_eq!(errors(&data), vec![]);
+        data.symbol = "S".repeat(11);
+        assert_eq!(
+            errors(&data),
+            vec![ValidationError::SymbolTooLong { len: 11 }]
+        );
+    }
+
+    #[test]
+    fn uri_at_limit_passes_and_one_over_fails() {
+        let mut data = data();
+        data.uri = "u".repeat(200);
+        assert_eq!(errors(&data), vec![]);
+        data.uri = "u".repeat(201);
+        assert_eq!(
+            errors(&data),
+            vec![ValidationError::UriTooLong { len: 201 }]
+        );
+    }
+
+    #[test]
+    fn royalty_at_limit_passes_and_one_over_fails() {
+        let mut data = data();
+        data.seller_fee_basis_points = 10_000;
+        assert_eq!(errors(&data), vec![]);
+        data.seller_fee_basis_points = 10_001;
+        assert_eq!(
+            errors(&data),
+            vec![ValidationError::RoyaltyTooHigh {
+                basis_points: 10_001
+            }]
+        );
+    }
+
+    #[test]
+    fn empty_creator_list_fails() {
+        let mut data = data();
+        data.creators = Some(vec![]);
+        assert_eq!(errors(&data), vec![ValidationError::NoCreators]);
+    }
+
+    #[test]
+    fn missing_creators_pass() {
+        let mut data = data();
+        data.creators = None;
+        assert_eq!(errors(&data), vec![]);
+    }
+
+    #[test]
+    fn five_creators_pass_and_six_fail() {
+        let five: Vec<Creator> = [20, 20, 20, 20, 20].map(creator).into();
+        assert_eq!(validate_creators(&five), vec![]);
+        let six: Vec<Creator> = [20, 20, 20, 20, 10, 10].map(creator).into();
+        assert_eq!(
+            validate_creators(&six),
+            vec![ValidationError::TooManyCreators { count: 6 }]
+        );
+    }
+
+    #[test]
+    fn duplicate_creator_is_reported_once() {
+        let first = creator(40);
+        let creators = vec![
+            first.clone(),
+            Creator {
+                share: 30,
+                ..first.clone()
+            },
+            Creator {
+                share: 30,
+                ..first.clone()
+            },
+        ];
+        assert_eq!(
+            validate_creators(&creators),
+            vec![ValidationError::DuplicateCreator {
+                address: first.address
+            }]
+        );
+    }
+
+    #[test]
+    fn shares_must_add_up_to_100() {
+        assert_eq!(
+            validate_creators(&[creator(50), creator(49)]),
+            vec![ValidationError::CreatorSharesNot100 { total: 99 }]
+        );
+        assert_eq!(
+            validate_creators(&[creator(50), creator(51)]),
+            vec![ValidationError::CreatorSharesNot100 { total: 101 }]
+        );
+        // Summed wider than u8, so overflowing shares are not wrapped to 100
+        assert_eq!(
+            validate_creators(&[creator(200), creator(156)]),
+            vec![ValidationError::CreatorSharesNot100 { total: 356 }]
+        );
+    }
+
+    #[test]
+    fn remaining_uses_at_total_pass_and_one_over_fail() {
+        assert_eq!(validate_uses(&uses(UseMethod::Multiple, 5, 5)), vec![]);
+        assert_eq!(
+            validate_uses(&uses(UseMethod::Multiple, 6, 5)),
+            vec![ValidationError::UsesRemainingAboveTotal {
+                remaining: 6,
+                total: 5
+            }]
+        );
+    }
+
+    #[test]
+    fn single_use_needs_a_total_of_1() {
+        assert_eq!(validate_uses(&uses(UseMethod::Single, 1, 1)), vec![]);
+        assert_eq!(
+            validate_uses(&uses(UseMethod::Single, 1, 2)),
+            vec![ValidationError::SingleUseTotalNot1 { total: 2 }]
+        );
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let mut data = data();
+        data.name = "n".repeat(33);
+        data.seller_fee_basis_points = 10_001;
+        data.uses = Some(uses(UseMethod::Single, 3, 2));
+        assert_eq!(
+            errors(&data),
+            vec![
+                ValidationError::NameTooLong { len: 33 },
+                ValidationError::RoyaltyTooHigh {
+                    basis_points: 10_001
+                },
+                ValidationError::UsesRemainingAboveTotal {
+                    remaining: 3,
+                    total: 2
+                },
+                ValidationError::SingleUseTotalNot1 { total: 2 },
+            ]
+        );
+    }
+}